@@ -1,6 +1,102 @@
 no-drives = No drives found
 show-on-panel = Show on panel
 enable-alerts = Enable alerts
-threshold = Threshold
+warning-threshold = Warning
+critical-threshold = Critical
 alert-title = Disk Space Warning
-alert-body = { $drive } is at { $percent }% capacity
\ No newline at end of file
+alert-critical-title = Disk Space Critical
+alert-body = { $drive } is at { $percent }% capacity
+not-responding = Not responding
+full-in = Full in { $time }
+predict-alert = Full within
+predict-off = Off
+alert-predicted-body = { $drive } is predicted to be full in { $time }
+time-minutes = ~{ $count } { $count ->
+    [one] minute
+   *[other] minutes
+}
+time-hours = ~{ $count } { $count ->
+    [one] hour
+   *[other] hours
+}
+time-days = ~{ $count } { $count ->
+    [one] day
+   *[other] days
+}
+range-hour = 1h
+range-day = 24h
+range-week = 7d
+range-month = 30d
+inodes-used = Inodes: { $used } / { $total } ({ $percent }%)
+inode-threshold = Inode threshold
+alert-inode-body = { $drive } has used { $percent }% of its inodes
+percent-thresholds = Percentage thresholds
+min-free = Critical below
+min-free-unset = None
+gib-free = GiB free
+alert-free-body = { $drive } has only { $free } free
+unmount = Unmount
+eject = Eject
+power-off = Power off
+mount = Mount
+unmounted-filesystems = Not mounted
+health-ok = Healthy
+health-failing = Failing
+power-on-hours = { $hours } h powered on
+bad-sectors = { $count } bad { $count ->
+    [one] sector
+   *[other] sectors
+}
+alert-health-title = Drive Health Warning
+alert-smart-body = { $drive } reports SMART failure; back up your data now
+alert-temperature-body = { $drive } is at { $temperature } °C
+monitor-network-mounts = Monitor network mounts
+btrfs-data = Data
+btrfs-metadata = Metadata
+btrfs-system = System
+btrfs-unallocated = Unallocated: { $size }
+metadata-threshold = Metadata threshold
+also-mounted-at = Also mounted at { $mounts }
+alert-btrfs-metadata-body = Btrfs metadata on { $drive } is { $percent }% full; writes may fail with "No space left on device"
+zfs-fragmentation = { $percent }% fragmented
+alert-pool-health-body = ZFS pool { $pool } is { $health }
+quota-used = Your quota: { $used } / { $limit } ({ $percent }%)
+project-quota-used = Project quota: { $used } / { $limit } ({ $percent }%)
+quota-threshold = Quota threshold
+alert-quota-body = You have used { $percent }% of your quota on { $drive }
+analyze-space = What's using space?
+scanning = Scanning…
+scan-cancelled = Scan cancelled
+rescan = Scan again
+cancel = Cancel
+close = Close
+analyzer-total = { $size } used here
+reclaim-space = Reclaim space
+reclaim-find = Find
+reclaim-nothing = Nothing to clean up
+reclaim-trash = Trash
+reclaim-cache = Cache
+reclaim-rust-targets = Unused Rust build files
+reclaim-flatpak = Unused Flatpak runtimes
+reclaim-journal = System logs
+reclaim-freed = Freed { $size }
+clean = Clean
+confirm = Confirm
+cleaning = Cleaning…
+notification-open = Open
+notification-details = Show details
+notification-snooze = Snooze 24h
+show-all-drives = Show all drives
+notify-recovery = Notify when space is recovered
+alert-recovered-title = Disk space recovered
+alert-recovered-body = { $drive } is back below its warning threshold
+pause-alerts = Pause alerts
+pause-1h = 1 h
+pause-4h = 4 h
+pause-tomorrow = Until tomorrow
+alerts-paused = Alerts paused for { $time }
+resume-alerts = Resume
+alert-quiet-summary-title = { $count ->
+    [one] 1 alert
+   *[other] { $count } alerts
+} during quiet hours
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Finds what is using space under a directory, like `du -x`.
+//!
+//! Scans run on a blocking worker, stay on the starting filesystem, and
+//! check a cancel flag between entries so a new scan can replace an old one.
+
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+/// Number of entries kept in a scan result.
+pub const TOP_ENTRIES: usize = 20;
+
+/// Bytes per block in `st_blocks`.
+const BLOCK_SIZE: u64 = 512;
+
+/// A file or directory and the space it uses.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// Bytes on disk, including everything below a directory.
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// The largest entries directly inside a directory.
+#[derive(Debug, Clone)]
+pub struct ScanResult {
+    /// Bytes on disk used under the directory.
+    pub total: u64,
+    /// Largest entries first, at most [`TOP_ENTRIES`].
+    pub entries: Vec<Entry>,
+}
+
+/// Scans a directory on a blocking worker.
+///
+/// Returns `Ok(None)` if `cancel` was set before the scan finished.
+pub async fn scan_async(path: PathBuf, cancel: Arc<AtomicBool>) -> Result<Option<ScanResult>> {
+    tokio::task::spawn_blocking(move || scan(&path, &cancel))
+        .await
+        .context("scan worker panicked")?
+}
+
+/// Sizes every entry directly inside `path` without crossing into other filesystems.
+///
+/// Returns `Ok(None)` if `cancel` was set before the scan finished.
+pub fn scan(path: &Path, cancel: &AtomicBool) -> Result<Option<ScanResult>> {
+    let root = fs::symlink_metadata(path).with_context(|| format!("failed to stat {}", path.display()))?;
+    let read_dir = fs::read_dir(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut scanner = Scanner {
+        dev: root.dev(),
+        seen: HashSet::new(),
+        cancel,
+    };
+
+    let mut entries = Vec::new();
+    for child in read_dir.flatten() {
+        let Ok(meta) = child.metadata() else {
+            continue;
+        };
+        // Other filesystems mounted below this one are left out, like `du -x`
+        if meta.dev() != scanner.dev {
+            continue;
+        }
+
+        let Some(size) = scanner.size_of(&child.path(), &meta) else {
+            return Ok(None);
+        };
+        entries.push(Entry {
+            path: child.path(),
+            size,
+            is_dir: meta.is_dir(),
+        });
+    }
+
+    let total = entries.iter().map(|e| e.size).sum::<u64>() + disk_usage(&root);
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+    entries.truncate(TOP_ENTRIES);
+
+    Ok(Some(ScanResult {
+        total,
+        entries,
+    }))
+}
+
+/// Returns the bytes used by `path` and everything below it on the same filesystem.
+pub fn disk_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    let cancel = AtomicBool::new(false);
+    let mut scanner = Scanner {
+        dev: meta.dev(),
+        seen: HashSet::new(),
+        cancel: &cancel,
+    };
+    scanner.size_of(path, &meta).unwrap_or(0)
+}
+
+struct Scanner<'a> {
+    /// Device of the directory being scanned.
+    dev: u64,
+    /// Hard-linked inodes already counted.
+    seen: HashSet<u64>,
+    cancel: &'a AtomicBool,
+}
+
+impl Scanner<'_> {
+    /// Returns the bytes used by `path` and everything below it, or `None` if cancelled.
+    ///
+    /// Unreadable directories count only their own size.
+    fn size_of(&mut self, path: &Path, meta: &fs::Metadata) -> Option<u64> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        if meta.nlink() > 1 && !meta.is_dir() && !self.seen.insert(meta.ino()) {
+            return Some(0);
+        }
+
+        let mut size = disk_usage(meta);
+        if !meta.is_dir() {
+            return Some(size);
+        }
+
+        let Ok(read_dir) = fs::read_dir(path) else {
+            return Some(size);
+        };
+
+        for child in read_dir.flatten() {
+            let Ok(child_meta) = child.metadata() else {
+                continue;
+            };
+            if child_meta.dev() != self.dev {
+                continue;
+            }
+            size += self.size_of(&child.path(), &child_meta)?;
+        }
+
+        Some(size)
+    }
+}
+
+fn disk_usage(meta: &fs::Metadata) -> u64 {
+    meta.blocks() * BLOCK_SIZE
+}
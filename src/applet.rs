@@ -1,27 +1,186 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant};
 
 use cosmic::app::{Core, Task};
 use cosmic::cosmic_config::{Config as CosmicConfig, CosmicConfigEntry};
 use cosmic::iced::platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup};
 use cosmic::iced::window::Id;
-use cosmic::iced::{time, Limits, Subscription, Length};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, time, Limits, Subscription, Length};
 use cosmic::widget::{self, container, text};
 use cosmic::{theme, Application, Element, Theme};
 
-use crate::config::Config;
+use crate::analyzer::{self, ScanResult};
+use crate::btrfs::{self, BtrfsUsage};
+use crate::cleanup::{self, CleanupKind, Reclaimable};
+use crate::config::{self, AlertLevel, Config, DriveAlertConfig, NotificationsConfig};
 use crate::fl;
-use crate::space::{self, SpaceInfo};
-use crate::udisks::{self, DriveInfo};
+use crate::history::{self, HistoryStore, Sample};
+use crate::hooks::{self, HookContext, HookEvent};
+use crate::localtime;
+use crate::metrics::{self, DriveMetrics};
+use crate::mountinfo;
+use crate::notifications;
+use crate::service::{self, DriveSnapshot, SharedDrives};
+use crate::space::{self, QuotaInfo, SpaceInfo};
+use crate::udisks::{self, DriveInfo, UnmountedFs};
+use crate::zfs::{self, PoolInfo};
 
 /// Combined drive and space data for display.
 #[derive(Debug, Clone)]
 pub struct DriveStatus {
     pub info: DriveInfo,
     pub space: SpaceInfo,
+    /// True if statvfs timed out and `space` holds the last known values.
+    pub unresponsive: bool,
+    /// Predicted time until the drive is full, from its usage history.
+    pub time_to_full: Option<Duration>,
+    /// Peak usage percentage in each usage graph column, oldest first.
+    pub graph: Vec<Option<u8>>,
+    /// Block group allocation, for btrfs filesystems.
+    pub btrfs: Option<BtrfsUsage>,
+    /// The current user's quota on this filesystem, if one applies.
+    pub quota: Option<QuotaInfo>,
+}
+
+/// Conditions that are alerted on independently for each drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum AlertKind {
+    /// Usage reached the drive's warning threshold.
+    UsageWarning,
+    /// Usage reached the drive's critical threshold.
+    UsageCritical,
+    /// Usage is predicted to reach 100% within the configured window.
+    PredictedFull,
+    /// Inode usage reached the drive's inode threshold.
+    Inodes,
+    /// Btrfs metadata reached the drive's metadata threshold.
+    BtrfsMetadata,
+    /// The user's quota reached the drive's quota threshold.
+    Quota,
+    /// The user's quota is used up.
+    QuotaExceeded,
+    /// A ZFS pool is degraded or faulted.
+    PoolHealth,
+    /// SMART reports the physical drive is failing.
+    SmartFailing,
+    /// The physical drive is hotter than the configured limit.
+    Temperature,
+}
+
+/// How long the "Snooze 24h" notification action silences a drive.
+const SNOOZE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The drive or pool a notification is about, for its action buttons.
+#[derive(Debug, Clone)]
+pub struct AlertTarget {
+    /// Drive or pool id, used to snooze it and to show its card.
+    id: String,
+    /// Mount point opened by the "Open" action; pools have none.
+    mount_point: Option<PathBuf>,
+}
+
+/// How long the popup's "Pause alerts" buttons silence notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PausePeriod {
+    Hour,
+    FourHours,
+    /// Until local midnight.
+    UntilTomorrow,
+}
+
+impl PausePeriod {
+    const ALL: [Self; 3] = [Self::Hour, Self::FourHours, Self::UntilTomorrow];
+
+    fn duration(self) -> Duration {
+        const HOUR: Duration = Duration::from_secs(60 * 60);
+        match self {
+            Self::Hour => HOUR,
+            Self::FourHours => 4 * HOUR,
+            Self::UntilTomorrow => localtime::until_midnight().unwrap_or(24 * HOUR),
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Self::Hour => fl!("pause-1h"),
+            Self::FourHours => fl!("pause-4h"),
+            Self::UntilTomorrow => fl!("pause-tomorrow"),
+        }
+    }
+}
+
+/// Actions that can be run on a mounted drive from its card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveAction {
+    Unmount,
+    Eject,
+    PowerOff,
+}
+
+/// Time span shown by the usage graph in each drive card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphRange {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl GraphRange {
+    const ALL: [Self; 4] = [Self::Hour, Self::Day, Self::Week, Self::Month];
+
+    fn duration(self) -> Duration {
+        const HOUR: u64 = 60 * 60;
+        Duration::from_secs(match self {
+            Self::Hour => HOUR,
+            Self::Day => 24 * HOUR,
+            Self::Week => 7 * 24 * HOUR,
+            Self::Month => 30 * 24 * HOUR,
+        })
+    }
+
+    fn label(self) -> String {
+        match self {
+            Self::Hour => fl!("range-hour"),
+            Self::Day => fl!("range-day"),
+            Self::Week => fl!("range-week"),
+            Self::Month => fl!("range-month"),
+        }
+    }
+}
+
+/// State of the "What's using space?" page.
+struct Analyzer {
+    /// Mount point the analysis started from; going back stops here.
+    root: PathBuf,
+    /// Directory currently shown.
+    path: PathBuf,
+    /// Set to stop the running scan.
+    cancel: Arc<AtomicBool>,
+    scanning: bool,
+    result: Option<ScanResult>,
+    error: Option<String>,
+}
+
+/// State of the "Reclaim space" section.
+#[derive(Default)]
+struct Reclaim {
+    scanning: bool,
+    /// Items found by the last scan, or `None` before the first scan.
+    items: Option<Vec<Reclaimable>>,
+    /// Item waiting for the user to confirm cleaning.
+    confirming: Option<CleanupKind>,
+    /// Items being cleaned.
+    cleaning: HashSet<CleanupKind>,
+    /// Outcome of the last cleanup of each item: bytes freed or an error.
+    results: HashMap<CleanupKind, Result<u64, String>>,
 }
 
 /// Tracks alert state for a drive to implement cooldown.
@@ -31,13 +190,87 @@ struct AlertState {
     was_over_threshold: bool,
 }
 
+/// Non-critical alerts held back during quiet hours, delivered as one
+/// notification when they end.
+#[derive(Debug, Default)]
+struct QuietQueue {
+    /// Alert bodies keyed like `alert_states`, so a repeated alert replaces
+    /// the older one and a cleared one can be dropped.
+    alerts: Vec<((PathBuf, AlertKind), String)>,
+}
+
+impl QuietQueue {
+    /// Holds back an alert, replacing an older one with the same state key.
+    fn push(&mut self, key: (PathBuf, AlertKind), body: String) {
+        self.alerts.retain(|(queued, _)| *queued != key);
+        self.alerts.push((key, body));
+    }
+
+    /// Drops alerts whose condition has cleared since they were queued.
+    fn retain_active(&mut self, states: &HashMap<(PathBuf, AlertKind), AlertState>) {
+        self.alerts
+            .retain(|(key, _)| states.get(key).is_some_and(|state| state.was_over_threshold));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.alerts.is_empty()
+    }
+
+    /// Removes and returns the held-back alert bodies, oldest first.
+    fn take(&mut self) -> Vec<String> {
+        self.alerts.drain(..).map(|(_, body)| body).collect()
+    }
+}
+
 pub struct CargoWatch {
     core: Core,
     popup: Option<Id>,
     config: Config,
     config_handler: Option<CosmicConfig>,
+    /// All mounted drives reported by UDisks2, before filtering.
+    mounted: Vec<DriveInfo>,
+    /// Filesystems UDisks2 can mount, shown below the drive cards.
+    unmounted: Vec<UnmountedFs>,
     drives: Vec<DriveStatus>,
-    alert_states: HashMap<PathBuf, AlertState>,
+    /// Imported ZFS pools, refreshed while any dataset is monitored.
+    pools: Vec<PoolInfo>,
+    /// Drive ids or object paths with a mount/unmount action in progress.
+    pending_actions: HashSet<String>,
+    /// Last action error per drive id or object path, shown inline.
+    action_errors: HashMap<String, String>,
+    /// Shared with the refresh task, which records samples on a blocking worker.
+    history: Arc<Mutex<HistoryStore>>,
+    graph_range: GraphRange,
+    alert_states: HashMap<(PathBuf, AlertKind), AlertState>,
+    /// Unsaved text in each drive's minimum free space field.
+    min_free_drafts: HashMap<String, String>,
+    /// Space analyzer page, shown in place of the drive cards while open.
+    analyzer: Option<Analyzer>,
+    reclaim: Reclaim,
+    /// Drive or pool whose card the popup shows alone, from a notification's
+    /// "Show details" action.
+    focused_drive: Option<String>,
+    /// Drive and pool ids whose alerts are snoozed, and until when.
+    snoozed_until: HashMap<String, Instant>,
+    /// Alerts for every drive and pool, including ones that appear later,
+    /// are snoozed until this time.
+    all_snoozed_until: Option<Instant>,
+    /// Id of the last usage notification per drive or pool id, replaced by
+    /// the recovery notification.
+    usage_notifications: HashMap<String, u32>,
+    /// Drive status published by the D-Bus service.
+    service_drives: SharedDrives,
+    /// Session bus connection owning the service name, once started.
+    service: Option<zbus::Connection>,
+    /// Session bus connection alerts are sent on, once its action listener is up.
+    notifier: Option<zbus::Connection>,
+    /// Drive or pool each shown alert notification is about, by notification id.
+    notification_targets: HashMap<u32, AlertTarget>,
+    /// All notifications are suppressed until this time.
+    alerts_paused_until: Option<Instant>,
+    quiet_queue: QuietQueue,
+    /// Whether COSMIC's Do Not Disturb mode is on.
+    do_not_disturb: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -45,12 +278,55 @@ pub enum Message {
     TogglePopup,
     PopupClosed(Id),
     Tick,
+    MountsChanged(Vec<DriveInfo>, Vec<UnmountedFs>),
+    /// Network mounts and ZFS datasets from the mount table.
+    MountTableRead(Vec<DriveInfo>),
+    DrivesRefreshed(Vec<DriveStatus>, Option<Vec<PoolInfo>>),
     OpenFileManager(PathBuf),
     TogglePanelDrive(String, bool),
     ToggleDriveAlert(String, bool),
-    SetDriveThreshold(String, u8),
+    SetDriveWarningThreshold(String, u8),
+    SetDriveCriticalThreshold(String, u8),
+    ToggleDrivePercent(String, bool),
+    /// The minimum free space field was edited; saved by [`Message::SetDriveMinFree`].
+    EditDriveMinFree(String, String),
+    SetDriveMinFree(String),
+    SetDrivePrediction(String, u32),
+    SetDriveInodeThreshold(String, u8),
+    SetDriveQuotaThreshold(String, u8),
+    SetDriveMetadataThreshold(String, u8),
+    SetGraphRange(GraphRange),
+    ToggleNetworkMounts(bool),
+    ToggleRecoveryNotifications(bool),
+    /// Pauses alerts for a period, or resumes them if `None`.
+    PauseAlerts(Option<PausePeriod>),
+    AnalyzeDrive(PathBuf),
+    AnalyzePath(PathBuf),
+    /// A scan finished, tagged with its cancel flag to tell it apart from
+    /// other scans, including earlier ones of the same directory.
+    AnalysisFinished(Arc<AtomicBool>, Result<Option<ScanResult>, String>),
+    CancelAnalysis,
+    CloseAnalyzer,
+    FindReclaimable,
+    ReclaimableFound(Vec<Reclaimable>),
+    ConfirmCleanup(Option<CleanupKind>),
+    RunCleanup(CleanupKind),
+    CleanupFinished(CleanupKind, Result<u64, String>),
+    RunDriveAction(String, DriveAction),
+    MountFilesystem(String),
+    DriveActionFinished(String, Result<(), String>),
+    /// An alert notification was shown, with its id.
+    AlertShown(AlertTarget, AlertKind, Result<u32, String>),
+    NotifierReady(zbus::Connection),
+    /// A notification action button was pressed, or a notification closed.
+    NotificationEvent(notifications::Event),
+    ShowAllDrives,
+    ServiceReady(zbus::Connection),
+    ServiceRequest(service::Request),
     #[allow(dead_code)]
     ConfigChanged(Config),
+    /// COSMIC's notification settings changed, e.g. Do Not Disturb was toggled.
+    NotificationsConfigChanged(NotificationsConfig),
 }
 
 #[allow(mismatched_lifetime_syntaxes)]
@@ -70,30 +346,45 @@ impl Application for CargoWatch {
     }
 
     fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
-        let (config, config_handler) = match CosmicConfig::new(Self::APP_ID, Config::VERSION) {
-            Ok(handler) => {
-                let config = Config::get_entry(&handler).unwrap_or_default();
-                (config, Some(handler))
-            }
-            Err(why) => {
-                eprintln!("failed to load config: {why}");
-                (Config::default(), None)
-            }
-        };
+        let (config, config_handler) = Config::load(Self::APP_ID);
 
-        let mut app = CargoWatch {
+        let app = CargoWatch {
             core,
             popup: None,
             config,
             config_handler,
+            mounted: Vec::new(),
+            unmounted: Vec::new(),
             drives: Vec::new(),
+            pools: Vec::new(),
+            pending_actions: HashSet::new(),
+            action_errors: HashMap::new(),
+            history: Arc::new(Mutex::new(HistoryStore::new())),
+            graph_range: GraphRange::default(),
             alert_states: HashMap::new(),
+            min_free_drafts: HashMap::new(),
+            analyzer: None,
+            reclaim: Reclaim::default(),
+            focused_drive: None,
+            snoozed_until: HashMap::new(),
+            all_snoozed_until: None,
+            usage_notifications: HashMap::new(),
+            service_drives: SharedDrives::default(),
+            service: None,
+            notifier: None,
+            notification_targets: HashMap::new(),
+            alerts_paused_until: None,
+            quiet_queue: QuietQueue::default(),
+            do_not_disturb: NotificationsConfig::load().do_not_disturb,
         };
 
         // Initial drive scan
-        app.refresh_drives();
+        let task = cosmic::task::future(async {
+            let (mounted, unmounted) = scan_drives().await;
+            Message::MountsChanged(mounted, unmounted)
+        });
 
-        (app, Task::none())
+        (app, task)
     }
 
     fn on_close_requested(&self, id: Id) -> Option<Message> {
@@ -105,7 +396,7 @@ impl Application for CargoWatch {
         let panel_drives: Vec<_> = self
             .drives
             .iter()
-            .filter(|d| self.is_on_panel(&d.info.mount_point))
+            .filter(|d| self.is_on_panel(&d.info))
             .collect();
 
         if panel_drives.is_empty() {
@@ -130,15 +421,19 @@ impl Application for CargoWatch {
 
                 for drive in &panel_drives {
                     let name = drive.info.display_name();
-                    let pct = drive.space.percent_used();
-                    let mount_str = drive.info.mount_point.display().to_string();
-                    let alert_config = self.config.get_drive_alert(&mount_str);
-                    let is_warning = pct >= alert_config.threshold;
-
-                    let pct_text = if is_warning {
-                        text(format!("{pct}%")).class(theme::Text::Custom(danger_text_style))
-                    } else {
-                        text(format!("{pct}%"))
+                    let (pct, level) = self.usage_level(drive);
+
+                    let pct_text = match level {
+                        _ if drive.unresponsive => {
+                            text(format!("{pct}%")).class(theme::Text::Custom(dim_text_style))
+                        }
+                        AlertLevel::Critical => {
+                            text(format!("{pct}%")).class(theme::Text::Custom(danger_text_style))
+                        }
+                        AlertLevel::Warning => {
+                            text(format!("{pct}%")).class(theme::Text::Custom(warning_text_style))
+                        }
+                        AlertLevel::Normal => text(format!("{pct}%")),
                     };
 
                     let drive_display = widget::row::Row::new()
@@ -158,15 +453,19 @@ impl Application for CargoWatch {
 
                 for drive in &panel_drives {
                     let name = drive.info.display_name();
-                    let pct = drive.space.percent_used();
-                    let mount_str = drive.info.mount_point.display().to_string();
-                    let alert_config = self.config.get_drive_alert(&mount_str);
-                    let is_warning = pct >= alert_config.threshold;
-
-                    let pct_text = if is_warning {
-                        text(format!("{pct}%")).class(theme::Text::Custom(danger_text_style))
-                    } else {
-                        text(format!("{pct}%"))
+                    let (pct, level) = self.usage_level(drive);
+
+                    let pct_text = match level {
+                        _ if drive.unresponsive => {
+                            text(format!("{pct}%")).class(theme::Text::Custom(dim_text_style))
+                        }
+                        AlertLevel::Critical => {
+                            text(format!("{pct}%")).class(theme::Text::Custom(danger_text_style))
+                        }
+                        AlertLevel::Warning => {
+                            text(format!("{pct}%")).class(theme::Text::Custom(warning_text_style))
+                        }
+                        AlertLevel::Normal => text(format!("{pct}%")),
                     };
 
                     col = col.push(
@@ -188,80 +487,399 @@ impl Application for CargoWatch {
     }
 
     fn view_window(&self, _id: Id) -> Element<Self::Message> {
+        if let Some(analyzer) = &self.analyzer {
+            return self.core.applet.popup_container(self.analyzer_view(analyzer)).into();
+        }
+
         let mut content = widget::column::Column::new().spacing(8).padding(12);
 
         if self.drives.is_empty() {
             content = content.push(text(fl!("no-drives")));
         } else {
-            for drive in &self.drives {
+            // Graph range selector shared by all cards
+            let mut range_row = widget::row::Row::new().spacing(4);
+            for range in GraphRange::ALL {
+                let class = if range == self.graph_range {
+                    theme::Button::Suggested
+                } else {
+                    theme::Button::Text
+                };
+                range_row = range_row.push(
+                    widget::button::text(range.label())
+                        .class(class)
+                        .on_press(Message::SetGraphRange(range)),
+                );
+            }
+            content = content.push(range_row);
+
+            if self.focused_drive.is_some() {
+                content = content.push(
+                    widget::button::text(fl!("show-all-drives"))
+                        .class(theme::Button::Link)
+                        .on_press(Message::ShowAllDrives),
+                );
+            }
+
+            let shown = |id: &str| self.focused_drive.as_deref().is_none_or(|focused| focused == id);
+
+            // Datasets of a listed pool are shown on its card below
+            for drive in self.drives.iter().filter(|d| !self.in_listed_pool(&d.info) && shown(&d.info.id)) {
                 let name = drive.info.display_name();
                 let pct = drive.space.percent_used();
                 let used = space::format_bytes(drive.space.used);
                 let total = space::format_bytes(drive.space.total);
                 let mount = drive.info.mount_point.clone();
-                let mount_str = mount.display().to_string();
+                let id = drive.info.id.clone();
 
-                let alert_config = self.config.get_drive_alert(&mount_str);
-                let is_warning = pct >= alert_config.threshold;
-                let is_on_panel = self.is_on_panel(&mount);
+                let alert_config = self.config.get_drive_alert(&id);
+                let level = alert_config.level(&drive.space);
+                let is_on_panel = self.is_on_panel(&drive.info);
 
                 // Clones for closures
-                let mount_str_panel = mount_str.clone();
-                let mount_str_alert = mount_str.clone();
-                let mount_str_threshold = mount_str.clone();
+                let id_panel = id.clone();
+                let id_alert = id.clone();
+                let id_warning = id.clone();
+                let id_critical = id.clone();
+                let id_percent = id.clone();
+                let id_min_free = id.clone();
+                let id_min_free_submit = id.clone();
+                let id_prediction = id.clone();
+                let id_inodes = id.clone();
+                let id_quota = id.clone();
+                let id_metadata = id.clone();
 
                 // Checkbox for panel visibility
                 let panel_toggle = widget::checkbox(fl!("show-on-panel"), is_on_panel)
                     .on_toggle(move |checked| {
-                        Message::TogglePanelDrive(mount_str_panel.clone(), checked)
+                        Message::TogglePanelDrive(id_panel.clone(), checked)
                     })
                     .size(14);
 
                 // Checkbox for alert enable/disable
                 let alert_toggle = widget::checkbox(fl!("enable-alerts"), alert_config.enabled)
                     .on_toggle(move |checked| {
-                        Message::ToggleDriveAlert(mount_str_alert.clone(), checked)
+                        Message::ToggleDriveAlert(id_alert.clone(), checked)
+                    })
+                    .size(14);
+
+                // Warning and critical threshold sliders
+                let warning_row = widget::row::Row::new()
+                    .spacing(8)
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(text(fl!("warning-threshold")).size(12))
+                    .push(
+                        widget::slider(50..=99, alert_config.warning_threshold, move |val| {
+                            Message::SetDriveWarningThreshold(id_warning.clone(), val)
+                        })
+                        .width(Length::Fixed(100.0)),
+                    )
+                    .push(text(format!("{}%", alert_config.warning_threshold)).size(12));
+
+                let critical_row = widget::row::Row::new()
+                    .spacing(8)
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(text(fl!("critical-threshold")).size(12))
+                    .push(
+                        widget::slider(50..=99, alert_config.critical_threshold, move |val| {
+                            Message::SetDriveCriticalThreshold(id_critical.clone(), val)
+                        })
+                        .width(Length::Fixed(100.0)),
+                    )
+                    .push(text(format!("{}%", alert_config.critical_threshold)).size(12));
+
+                // Percentage rule toggle and absolute free space rule
+                let percent_toggle = widget::checkbox(fl!("percent-thresholds"), alert_config.percent_enabled)
+                    .on_toggle(move |checked| {
+                        Message::ToggleDrivePercent(id_percent.clone(), checked)
                     })
                     .size(14);
 
-                // Threshold slider
-                let threshold_row = widget::row::Row::new()
+                // Show unsaved edits until they're submitted or the field loses focus
+                let min_free_value = self.min_free_drafts.get(&id).cloned().unwrap_or_else(|| {
+                    alert_config
+                        .min_free_gib
+                        .map(|gib| gib.to_string())
+                        .unwrap_or_default()
+                });
+                let min_free_row = widget::row::Row::new()
+                    .spacing(8)
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(text(fl!("min-free")).size(12))
+                    .push(
+                        widget::text_input(fl!("min-free-unset"), min_free_value)
+                            .on_input(move |val| {
+                                Message::EditDriveMinFree(id_min_free.clone(), val)
+                            })
+                            .on_submit(move |_| Message::SetDriveMinFree(id_min_free_submit.clone()))
+                            .on_unfocus(Message::SetDriveMinFree(id.clone()))
+                            .width(Length::Fixed(64.0)),
+                    )
+                    .push(text(fl!("gib-free")).size(12));
+
+                // Prediction slider; 0 turns the prediction alert off
+                let predict_hours = alert_config.predict_full_hours.unwrap_or(0);
+                let predict_label = if predict_hours == 0 {
+                    fl!("predict-off")
+                } else {
+                    format!("{predict_hours} h")
+                };
+                let prediction_row = widget::row::Row::new()
                     .spacing(8)
                     .align_y(cosmic::iced::Alignment::Center)
-                    .push(text(fl!("threshold")).size(12))
+                    .push(text(fl!("predict-alert")).size(12))
                     .push(
-                        widget::slider(50..=99, alert_config.threshold, move |val| {
-                            Message::SetDriveThreshold(mount_str_threshold.clone(), val)
+                        widget::slider(0..=168, predict_hours, move |val| {
+                            Message::SetDrivePrediction(id_prediction.clone(), val)
                         })
                         .width(Length::Fixed(100.0)),
                     )
-                    .push(text(format!("{}%", alert_config.threshold)).size(12));
+                    .push(text(predict_label).size(12));
+
+                // Grey out drives whose last statvfs timed out, keeping last known values
+                let usage_text = if drive.unresponsive {
+                    text(format!("{used} / {total}")).class(theme::Text::Custom(dim_text_style))
+                } else {
+                    text(format!("{used} / {total}"))
+                };
 
                 let header_row = widget::row::Row::new()
+                    .spacing(6)
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(widget::icon::from_name(drive_icon(&drive.info)).size(16).icon())
                     .push(text(name).size(14))
                     .push(widget::horizontal_space())
-                    .push(text(format!("{used} / {total}")).size(12));
+                    .push(usage_text.size(12));
 
                 let bar = widget::progress_bar(0.0..=100.0, pct as f32).height(8);
 
-                let bar_widget: Element<Self::Message> = if is_warning {
-                    bar.class(theme::ProgressBar::Danger).into()
-                } else {
-                    bar.into()
+                let bar_widget: Element<Self::Message> = match level {
+                    _ if drive.unresponsive => {
+                        bar.class(theme::ProgressBar::Custom(Box::new(stale_bar_style))).into()
+                    }
+                    AlertLevel::Critical => bar.class(theme::ProgressBar::Danger).into(),
+                    AlertLevel::Warning => {
+                        bar.class(theme::ProgressBar::Custom(Box::new(warning_bar_style))).into()
+                    }
+                    AlertLevel::Normal => bar.into(),
                 };
 
-                let footer_row = widget::row::Row::new()
+                let mut footer_row = widget::row::Row::new()
+                    .spacing(8)
                     .push(text(drive.info.mount_point.display().to_string()).size(11))
-                    .push(widget::horizontal_space())
-                    .push(text(format!("{pct}%")).size(12));
+                    .push(widget::horizontal_space());
 
-                // Info section is clickable to open file manager
-                let info_content = widget::column::Column::new()
+                if drive.unresponsive {
+                    footer_row = footer_row.push(
+                        text(fl!("not-responding"))
+                            .size(11)
+                            .class(theme::Text::Custom(dim_text_style)),
+                    );
+                } else if let Some(eta) = drive.time_to_full {
+                    footer_row = footer_row
+                        .push(text(fl!("full-in", time = format_duration(eta))).size(11));
+                }
+
+                let footer_row = footer_row.push(text(format!("{pct}%")).size(12));
+
+                // Inode usage line and threshold, only for filesystems with a fixed inode table
+                let inode_rows = drive.space.has_inodes().then(|| {
+                    let inode_pct = drive.space.inodes_percent_used();
+                    let inode_text = fl!(
+                        "inodes-used",
+                        used = space::format_count(drive.space.inodes_used),
+                        total = space::format_count(drive.space.inodes_total),
+                        percent = inode_pct.to_string()
+                    );
+                    let inode_text = if inode_pct >= alert_config.inode_threshold {
+                        text(inode_text).class(theme::Text::Custom(danger_text_style))
+                    } else {
+                        text(inode_text)
+                    };
+
+                    let threshold_row = widget::row::Row::new()
+                        .spacing(8)
+                        .align_y(cosmic::iced::Alignment::Center)
+                        .push(text(fl!("inode-threshold")).size(12))
+                        .push(
+                            widget::slider(50..=99, alert_config.inode_threshold, move |val| {
+                                Message::SetDriveInodeThreshold(id_inodes.clone(), val)
+                            })
+                            .width(Length::Fixed(100.0)),
+                        )
+                        .push(text(format!("{}%", alert_config.inode_threshold)).size(12));
+
+                    (inode_text.size(11), threshold_row)
+                });
+
+                let graph = usage_graph(&drive.graph, &alert_config);
+
+                let (inode_text, inode_threshold_row) = inode_rows.unzip();
+
+                // User quota as a second bar, with its own threshold
+                let quota_rows = drive.quota.as_ref().map(|quota| {
+                    let quota_pct = quota.percent_used();
+                    let label = if quota.project {
+                        fl!(
+                            "project-quota-used",
+                            used = space::format_bytes(quota.used),
+                            limit = space::format_bytes(quota.limit),
+                            percent = quota_pct.to_string()
+                        )
+                    } else {
+                        fl!(
+                            "quota-used",
+                            used = space::format_bytes(quota.used),
+                            limit = space::format_bytes(quota.limit),
+                            percent = quota_pct.to_string()
+                        )
+                    };
+
+                    let quota_bar = widget::progress_bar(0.0..=100.0, quota_pct as f32).height(6);
+                    let (label, quota_bar): (_, Element<Self::Message>) = match alert_config.quota_level(quota_pct) {
+                        AlertLevel::Critical => (
+                            text(label).class(theme::Text::Custom(danger_text_style)),
+                            quota_bar.class(theme::ProgressBar::Danger).into(),
+                        ),
+                        AlertLevel::Warning => (
+                            text(label).class(theme::Text::Custom(warning_text_style)),
+                            quota_bar.class(theme::ProgressBar::Custom(Box::new(warning_bar_style))).into(),
+                        ),
+                        AlertLevel::Normal => (text(label), quota_bar.into()),
+                    };
+
+                    let quota_column = widget::column::Column::new()
+                        .spacing(2)
+                        .push(label.size(11))
+                        .push(quota_bar);
+
+                    let threshold_row = widget::row::Row::new()
+                        .spacing(8)
+                        .align_y(cosmic::iced::Alignment::Center)
+                        .push(text(fl!("quota-threshold")).size(12))
+                        .push(
+                            widget::slider(50..=99, alert_config.quota_threshold, move |val| {
+                                Message::SetDriveQuotaThreshold(id_quota.clone(), val)
+                            })
+                            .width(Length::Fixed(100.0)),
+                        )
+                        .push(text(format!("{}%", alert_config.quota_threshold)).size(12));
+
+                    (quota_column, threshold_row)
+                });
+                let (quota_column, quota_threshold_row) = quota_rows.unzip();
+
+                let metadata_threshold_row = drive.btrfs.is_some().then(|| {
+                    widget::row::Row::new()
+                        .spacing(8)
+                        .align_y(cosmic::iced::Alignment::Center)
+                        .push(text(fl!("metadata-threshold")).size(12))
+                        .push(
+                            widget::slider(50..=99, alert_config.metadata_threshold, move |val| {
+                                Message::SetDriveMetadataThreshold(id_metadata.clone(), val)
+                            })
+                            .width(Length::Fixed(100.0)),
+                        )
+                        .push(text(format!("{}%", alert_config.metadata_threshold)).size(12))
+                });
+
+                let mut info_content = widget::column::Column::new()
                     .spacing(4)
                     .push(header_row)
                     .push(bar_widget)
+                    .push(graph)
                     .push(footer_row);
 
+                if let Some(quota_column) = quota_column {
+                    info_content = info_content.push(quota_column);
+                }
+
+                if let Some(inode_text) = inode_text {
+                    info_content = info_content.push(inode_text);
+                }
+
+                // Btrfs block group breakdown; metadata is what actually runs out
+                if let Some(btrfs) = &drive.btrfs {
+                    let metadata_level = alert_config.metadata_level(btrfs.metadata_percent_used());
+
+                    for (label, group, level) in [
+                        (fl!("btrfs-data"), &btrfs.data, AlertLevel::Normal),
+                        (fl!("btrfs-metadata"), &btrfs.metadata, metadata_level),
+                        (fl!("btrfs-system"), &btrfs.system, AlertLevel::Normal),
+                    ] {
+                        let mut line = format!(
+                            "{label}: {} / {}",
+                            space::format_bytes(group.used),
+                            space::format_bytes(group.total)
+                        );
+                        if let Some(profile) = &group.profile {
+                            line.push_str(&format!(" · {profile}"));
+                        }
+
+                        let line = text(line).size(11);
+                        let line = match level {
+                            AlertLevel::Critical => line.class(theme::Text::Custom(danger_text_style)),
+                            AlertLevel::Warning => line.class(theme::Text::Custom(warning_text_style)),
+                            AlertLevel::Normal => line,
+                        };
+                        info_content = info_content.push(line);
+                    }
+
+                    info_content = info_content.push(
+                        text(fl!("btrfs-unallocated", size = space::format_bytes(btrfs.unallocated))).size(11),
+                    );
+                }
+
+                // Subvolumes and other mounts of the same filesystem share this card
+                if !drive.info.also_mounted_at.is_empty() {
+                    let mounts: Vec<_> = drive
+                        .info
+                        .also_mounted_at
+                        .iter()
+                        .map(|mount| mount.display().to_string())
+                        .collect();
+                    info_content = info_content
+                        .push(text(fl!("also-mounted-at", mounts = mounts.join(", "))).size(11));
+                }
+
+                // SMART health badge and temperature
+                if let Some(health) = &drive.info.health {
+                    let badge = if health.failing {
+                        text(fl!("health-failing")).class(theme::Text::Custom(danger_text_style))
+                    } else {
+                        text(fl!("health-ok")).class(theme::Text::Custom(success_text_style))
+                    };
+
+                    let mut health_row = widget::row::Row::new()
+                        .spacing(8)
+                        .push(badge.size(11));
+
+                    if let Some(celsius) = health.temperature {
+                        let temperature = text(format!("{celsius:.0} °C"));
+                        let temperature = if celsius > f64::from(self.config.temperature_limit) {
+                            temperature.class(theme::Text::Custom(warning_text_style))
+                        } else {
+                            temperature
+                        };
+                        health_row = health_row.push(temperature.size(11));
+                    }
+
+                    if let Some(secs) = health.power_on_seconds {
+                        health_row = health_row
+                            .push(text(fl!("power-on-hours", hours = secs / 3600)).size(11));
+                    }
+
+                    if let Some(count) = health.bad_sectors.filter(|&count| count > 0) {
+                        health_row = health_row.push(
+                            text(fl!("bad-sectors", count = count))
+                                .size(11)
+                                .class(theme::Text::Custom(warning_text_style)),
+                        );
+                    }
+
+                    info_content = info_content.push(health_row);
+                }
+
+                // Info section is clickable to open file manager
                 let clickable_info = widget::mouse_area(info_content)
                     .on_press(Message::OpenFileManager(mount));
 
@@ -272,12 +890,65 @@ impl Application for CargoWatch {
                     .push(alert_toggle);
 
                 // Card contains clickable info + divider + settings
-                let card_content = widget::column::Column::new()
+                let mut card_content = widget::column::Column::new()
                     .spacing(6)
                     .push(clickable_info)
                     .push(widget::divider::horizontal::light())
                     .push(settings_row)
-                    .push(threshold_row);
+                    .push(percent_toggle)
+                    .push(warning_row)
+                    .push(critical_row)
+                    .push(min_free_row);
+
+                if let Some(inode_threshold_row) = inode_threshold_row {
+                    card_content = card_content.push(inode_threshold_row);
+                }
+
+                if let Some(quota_threshold_row) = quota_threshold_row {
+                    card_content = card_content.push(quota_threshold_row);
+                }
+
+                if let Some(metadata_threshold_row) = metadata_threshold_row {
+                    card_content = card_content.push(metadata_threshold_row);
+                }
+
+                let mut card_content = card_content.push(prediction_row);
+
+                // Space analyzer, then unmount and eject/power off for removable drives
+                let mut actions_row = widget::row::Row::new().spacing(4).push(
+                    widget::button::text(fl!("analyze-space"))
+                        .on_press(Message::AnalyzeDrive(drive.info.mount_point.clone())),
+                );
+
+                if drive.info.user_unmountable() {
+                    let busy = self.pending_actions.contains(&id);
+                    let action_button = |label: String, action: DriveAction| {
+                        widget::button::text(label)
+                            .on_press_maybe((!busy).then(|| Message::RunDriveAction(id.clone(), action)))
+                    };
+
+                    actions_row = actions_row.push(action_button(fl!("unmount"), DriveAction::Unmount));
+
+                    if drive.info.removable && drive.info.drive_path.is_some() {
+                        if drive.info.ejectable {
+                            actions_row = actions_row.push(action_button(fl!("eject"), DriveAction::Eject));
+                        }
+                        if drive.info.can_power_off {
+                            actions_row =
+                                actions_row.push(action_button(fl!("power-off"), DriveAction::PowerOff));
+                        }
+                    }
+                }
+
+                card_content = card_content.push(actions_row);
+
+                if let Some(why) = self.action_errors.get(&id) {
+                    card_content = card_content.push(
+                        text(why.clone())
+                            .size(11)
+                            .class(theme::Text::Custom(danger_text_style)),
+                    );
+                }
 
                 let card = container(card_content)
                     .padding(8)
@@ -286,7 +957,98 @@ impl Application for CargoWatch {
 
                 content = content.push(card);
             }
+
+            // ZFS pools, with their datasets listed underneath
+            for pool in self.pools.iter().filter(|pool| shown(&pool.id())) {
+                let datasets: Vec<_> = self
+                    .drives
+                    .iter()
+                    .filter(|d| d.info.is_zfs() && zfs::pool_name(&d.info.device) == pool.name)
+                    .collect();
+                if !datasets.is_empty() {
+                    content = content.push(self.pool_card(pool, &datasets));
+                }
+            }
+        }
+
+        // Filesystems that can be mounted from here
+        if !self.unmounted.is_empty() {
+            let mut list = widget::column::Column::new()
+                .spacing(6)
+                .push(text(fl!("unmounted-filesystems")).size(14));
+
+            for fs in &self.unmounted {
+                let key = fs.object_path.to_string();
+                let busy = self.pending_actions.contains(&key);
+                let details = format!(
+                    "{} · {} · {}",
+                    fs.device,
+                    fs.fs_type,
+                    space::format_bytes(fs.size)
+                );
+
+                let row = widget::row::Row::new()
+                    .spacing(8)
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(
+                        widget::column::Column::new()
+                            .push(text(fs.display_name()).size(13))
+                            .push(text(details).size(11)),
+                    )
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::text(fl!("mount"))
+                            .on_press_maybe((!busy).then(|| Message::MountFilesystem(key.clone()))),
+                    );
+                list = list.push(row);
+
+                if let Some(why) = self.action_errors.get(&key) {
+                    list = list.push(
+                        text(why.clone())
+                            .size(11)
+                            .class(theme::Text::Custom(danger_text_style)),
+                    );
+                }
+            }
+
+            content = content.push(
+                container(list)
+                    .padding(8)
+                    .width(Length::Fill)
+                    .class(theme::Container::Card),
+            );
+        }
+
+        content = content.push(self.reclaim_card());
+
+        // Global settings
+        let network_toggle = widget::checkbox(fl!("monitor-network-mounts"), self.config.monitor_network_mounts)
+            .on_toggle(Message::ToggleNetworkMounts)
+            .size(14);
+        let recovery_toggle = widget::checkbox(fl!("notify-recovery"), self.config.notify_recovery)
+            .on_toggle(Message::ToggleRecoveryNotifications)
+            .size(14);
+        content = content.push(network_toggle).push(recovery_toggle);
+
+        let now = Instant::now();
+        let mut pause_row = widget::row::Row::new()
+            .spacing(4)
+            .align_y(cosmic::iced::Alignment::Center);
+        if let Some(until) = self.alerts_paused_until.filter(|until| *until > now) {
+            pause_row = pause_row
+                .push(text(fl!("alerts-paused", time = format_duration(until - now))).size(14))
+                .push(widget::horizontal_space())
+                .push(widget::button::text(fl!("resume-alerts")).on_press(Message::PauseAlerts(None)));
+        } else {
+            pause_row = pause_row
+                .push(text(fl!("pause-alerts")).size(14))
+                .push(widget::horizontal_space());
+            for period in PausePeriod::ALL {
+                pause_row = pause_row
+                    .push(widget::button::text(period.label()).on_press(Message::PauseAlerts(Some(period))));
+            }
         }
+        content = content.push(pause_row);
 
         self.core.applet.popup_container(content).into()
     }
@@ -295,48 +1057,78 @@ impl Application for CargoWatch {
         match message {
             Message::TogglePopup => {
                 return if let Some(p) = self.popup.take() {
+                    self.close_analyzer();
+                    self.focused_drive = None;
                     destroy_popup(p)
                 } else {
-                    let new_id = Id::unique();
-                    self.popup.replace(new_id);
-                    let mut popup_settings = self.core.applet.get_popup_settings(
-                        self.core.main_window_id().unwrap(),
-                        new_id,
-                        None,
-                        None,
-                        None,
-                    );
-                    popup_settings.positioner.size_limits = Limits::NONE
-                        .max_width(372.0)
-                        .min_width(300.0)
-                        .min_height(100.0)
-                        .max_height(600.0);
-                    get_popup(popup_settings)
+                    self.open_popup()
                 };
             }
             Message::PopupClosed(id) => {
                 if self.popup.as_ref() == Some(&id) {
                     self.popup = None;
+                    self.close_analyzer();
+                    self.focused_drive = None;
                 }
             }
             Message::Tick => {
-                self.refresh_drives();
-                self.check_alerts();
+                // Network mounts and ZFS datasets don't raise UDisks2 signals, so re-read them on every tick
+                return self.refresh_mount_table();
+            }
+            Message::MountsChanged(mounted, unmounted) => {
+                // Move settings still keyed by mount point over to the drive's id
+                let mut migrated = false;
+                for info in &mounted {
+                    let mount = info.mount_point.display().to_string();
+                    migrated |= self.config.migrate_drive_key(&mount, &info.id);
+                }
+                if migrated {
+                    self.save_config();
+                }
+
+                // Keep the current network mounts and ZFS datasets until the mount table is re-read
+                let mounted: Vec<_> = mounted
+                    .into_iter()
+                    .chain(self.mounted.iter().filter(|d| d.network || d.is_zfs()).cloned())
+                    .collect();
+                self.mounted = mounted;
+                self.unmounted = unmounted;
+                return self.refresh_mount_table();
+            }
+            Message::MountTableRead(mounts) => {
+                self.mounted.retain(|d| !d.network && !d.is_zfs());
+                self.mounted.extend(mounts);
+                return self.refresh_space();
+            }
+            Message::DrivesRefreshed(drives, pools) => {
+                // Keep the last pool list if zpool timed out or failed
+                if let Some(pools) = pools {
+                    self.pools = pools;
+                }
+
+                self.drives = drives;
+                self.publish_drives();
+                self.write_metrics();
+                return self.check_alerts();
             }
             Message::OpenFileManager(path) => {
                 if let Err(why) = open::that(&path) {
                     eprintln!("failed to open file manager for {}: {why}", path.display());
                 }
             }
-            Message::TogglePanelDrive(mount, show) => {
+            Message::TogglePanelDrive(id, show) => {
+                let Some(info) = self.mounted.iter().find(|d| d.id == id).cloned() else {
+                    return Task::none();
+                };
                 if show {
-                    // Only add if not already matched (exact or prefix)
-                    if !self.is_on_panel(Path::new(&mount)) {
-                        self.config.panel_drives.push(mount);
+                    // Only add if not already matched (id, mount point or prefix)
+                    if !self.is_on_panel(&info) {
+                        self.config.panel_drives.push(id);
                     }
                 } else {
-                    // Remove exact match
-                    self.config.panel_drives.retain(|m| m != &mount);
+                    // Remove id and any entry still keyed by mount point
+                    let mount = info.mount_point.display().to_string();
+                    self.config.panel_drives.retain(|m| m != &id && m != &mount);
                     // Also remove any prefix that was matching this path
                     // (e.g., remove "/home" when unchecking "/home/john")
                     self.config.panel_drives.retain(|m| {
@@ -345,157 +1137,1643 @@ impl Application for CargoWatch {
                 }
                 self.save_config();
             }
-            Message::ToggleDriveAlert(mount, enabled) => {
-                let mut alert_config = self.config.get_drive_alert(&mount);
+            Message::ToggleDriveAlert(id, enabled) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
                 alert_config.enabled = enabled;
-                self.config.drive_alerts.insert(mount, alert_config);
+                self.config.drive_alerts.insert(id, alert_config);
                 self.save_config();
             }
-            Message::SetDriveThreshold(mount, threshold) => {
-                let mut alert_config = self.config.get_drive_alert(&mount);
-                alert_config.threshold = threshold;
-                self.config.drive_alerts.insert(mount, alert_config);
+            Message::SetDriveWarningThreshold(id, threshold) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.warning_threshold = threshold;
+                // Keep critical at or above warning
+                alert_config.critical_threshold = alert_config.critical_threshold.max(threshold);
+                self.config.drive_alerts.insert(id, alert_config);
                 self.save_config();
             }
-            Message::ConfigChanged(config) => {
-                self.config = config;
+            Message::SetDriveCriticalThreshold(id, threshold) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.critical_threshold = threshold;
+                // Keep warning at or below critical
+                alert_config.warning_threshold = alert_config.warning_threshold.min(threshold);
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
             }
-        }
-        Task::none()
-    }
-
-    fn subscription(&self) -> Subscription<Self::Message> {
-        time::every(Duration::from_secs(self.config.poll_interval)).map(|_| Message::Tick)
-    }
-
-    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
-        Some(cosmic::applet::style())
-    }
-}
-
-impl CargoWatch {
-    /// Returns true if the given mount point should be shown on the panel.
-    fn is_on_panel(&self, mount_point: &Path) -> bool {
-        let mount_str = mount_point.display().to_string();
-        self.config.panel_drives.iter().any(|m| {
-            m == &mount_str
-                || (m == "/home" && mount_str.starts_with("/home"))
-        })
-    }
-
-    /// Saves the current config to disk.
-    fn save_config(&self) {
-        if let Some(ref handler) = self.config_handler {
-            if let Err(why) = self.config.write_entry(handler) {
-                eprintln!("failed to save config: {why}");
+            Message::ToggleDrivePercent(id, enabled) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.percent_enabled = enabled;
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
             }
-        }
-    }
-
-    /// Refreshes drive list and space info.
-    fn refresh_drives(&mut self) {
-        let all_drives = match udisks::enumerate_drives() {
-            Ok(drives) => drives,
-            Err(why) => {
-                eprintln!("failed to enumerate drives: {why}");
-                return;
+            Message::EditDriveMinFree(id, input) => {
+                self.min_free_drafts.insert(id, input);
             }
-        };
-
-        // Filter to configured drives, or all non-removable if none configured
-        let filtered: Vec<_> = if self.config.monitored_drives.is_empty() {
-            all_drives.into_iter().filter(|d| !d.removable).collect()
-        } else {
-            all_drives
-                .into_iter()
-                .filter(|d| {
-                    self.config
-                        .monitored_drives
-                        .iter()
-                        .any(|m| d.mount_point == std::path::Path::new(m))
-                })
-                .collect()
-        };
-
-        // Get space info for each drive
-        self.drives = filtered
-            .into_iter()
-            .filter_map(|info| {
-                match space::get_space_info(&info.mount_point) {
-                    Ok(space) => Some(DriveStatus { info, space }),
-                    Err(why) => {
-                        eprintln!(
-                            "failed to get space for {}: {why}",
-                            info.mount_point.display()
-                        );
-                        None
-                    }
-                }
-            })
-            .collect();
-    }
-
-    /// Checks drives against alert threshold and sends notifications.
-    fn check_alerts(&mut self) {
-        let now = Instant::now();
-        let cooldown = Duration::from_secs(self.config.alert_cooldown);
-
-        // Collect alerts to send (avoids borrow conflict)
-        let mut alerts_to_send: Vec<(String, u8)> = Vec::new();
-
-        for drive in &self.drives {
-            let path = &drive.info.mount_point;
-            let mount_str = path.display().to_string();
-            let alert_config = self.config.get_drive_alert(&mount_str);
-
-            // Skip if alerts disabled for this drive
-            if !alert_config.enabled {
-                continue;
+            Message::SetDriveMinFree(id) => {
+                let Some(input) = self.min_free_drafts.remove(&id) else {
+                    return Task::none();
+                };
+                // Empty clears the rule; anything that isn't a whole number reverts the field
+                let min_free_gib = match input.trim() {
+                    "" => None,
+                    gib => match gib.parse::<u32>() {
+                        Ok(gib) => Some(gib),
+                        Err(_) => return Task::none(),
+                    },
+                };
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.min_free_gib = min_free_gib;
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
             }
+            Message::SetDrivePrediction(id, hours) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.predict_full_hours = (hours > 0).then_some(hours);
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
+            }
+            Message::SetDriveInodeThreshold(id, threshold) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.inode_threshold = threshold;
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
+            }
+            Message::SetDriveQuotaThreshold(id, threshold) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.quota_threshold = threshold;
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
+            }
+            Message::SetDriveMetadataThreshold(id, threshold) => {
+                let mut alert_config = self.config.get_drive_alert(&id);
+                alert_config.metadata_threshold = threshold;
+                self.config.drive_alerts.insert(id, alert_config);
+                self.save_config();
+            }
+            Message::SetGraphRange(range) => {
+                self.graph_range = range;
+                // Graphs are bucketed when drives refresh
+                return self.refresh_space();
+            }
+            Message::AnalyzeDrive(mount_point) => {
+                return self.start_analysis(mount_point.clone(), mount_point);
+            }
+            Message::AnalyzePath(path) => {
+                let Some(root) = self.analyzer.as_ref().map(|a| a.root.clone()) else {
+                    return Task::none();
+                };
+                return self.start_analysis(root, path);
+            }
+            Message::AnalysisFinished(cancel, result) => {
+                // Ignore scans the user has navigated away from or restarted
+                let Some(analyzer) = self.analyzer.as_mut().filter(|a| Arc::ptr_eq(&a.cancel, &cancel)) else {
+                    return Task::none();
+                };
+                analyzer.scanning = false;
+                match result {
+                    Ok(result) => analyzer.result = result,
+                    Err(why) => analyzer.error = Some(why),
+                }
+            }
+            Message::CancelAnalysis => {
+                if let Some(analyzer) = &mut self.analyzer {
+                    analyzer.cancel.store(true, Ordering::Relaxed);
+                    analyzer.scanning = false;
+                }
+            }
+            Message::CloseAnalyzer => {
+                self.close_analyzer();
+            }
+            Message::ToggleRecoveryNotifications(enabled) => {
+                self.config.notify_recovery = enabled;
+                self.save_config();
+            }
+            Message::PauseAlerts(period) => {
+                self.alerts_paused_until = period.map(|period| Instant::now() + period.duration());
+            }
+            Message::FindReclaimable => {
+                self.reclaim = Reclaim {
+                    scanning: true,
+                    ..Default::default()
+                };
+                let roots = self.config.cleanup_root_paths();
+                return cosmic::task::future(async move {
+                    let items = cleanup::find_reclaimable_async(roots).await.unwrap_or_else(|why| {
+                        eprintln!("failed to find reclaimable space: {why}");
+                        Vec::new()
+                    });
+                    Message::ReclaimableFound(items)
+                });
+            }
+            Message::ReclaimableFound(items) => {
+                self.reclaim.scanning = false;
+                self.reclaim.items = Some(items);
+            }
+            Message::ConfirmCleanup(kind) => {
+                self.reclaim.confirming = kind;
+            }
+            Message::RunCleanup(kind) => {
+                self.reclaim.confirming = None;
+                let Some(item) = self
+                    .reclaim
+                    .items
+                    .iter()
+                    .flatten()
+                    .find(|item| item.kind == kind)
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+                if !self.reclaim.cleaning.insert(kind) {
+                    return Task::none();
+                }
+                self.reclaim.results.remove(&kind);
 
-            let pct = drive.space.percent_used();
-            let over_threshold = pct >= alert_config.threshold;
+                return cosmic::task::future(async move {
+                    let result = cleanup::clean_async(item).await.map_err(|why| why.to_string());
+                    Message::CleanupFinished(kind, result)
+                });
+            }
+            Message::CleanupFinished(kind, result) => {
+                self.reclaim.cleaning.remove(&kind);
+                if let Err(why) = &result {
+                    eprintln!("cleanup failed: {why}");
+                }
+                self.reclaim.results.insert(kind, result);
+                // Show the new free space on the drive cards right away
+                return self.refresh_space();
+            }
+            Message::ToggleNetworkMounts(enabled) => {
+                self.config.monitor_network_mounts = enabled;
+                self.save_config();
+                return self.refresh_mount_table();
+            }
+            Message::RunDriveAction(id, action) => {
+                return self.run_drive_action(id, action);
+            }
+            Message::MountFilesystem(key) => {
+                let Some(fs) = self.unmounted.iter().find(|fs| fs.object_path.as_str() == key) else {
+                    return Task::none();
+                };
+                let object_path = fs.object_path.clone();
+                self.action_errors.remove(&key);
+                self.pending_actions.insert(key.clone());
+
+                return cosmic::task::future(async move {
+                    let result = udisks::mount(object_path).await.map(|_| ());
+                    Message::DriveActionFinished(key, result.map_err(|why| why.to_string()))
+                });
+            }
+            Message::DriveActionFinished(key, result) => {
+                // Mount changes arrive through the UDisks2 watcher; only errors are kept here
+                self.pending_actions.remove(&key);
+                if let Err(why) = result {
+                    eprintln!("drive action failed for {key}: {why}");
+                    self.action_errors.insert(key, why);
+                }
+            }
+            Message::AlertShown(target, kind, result) => match result {
+                Ok(notification_id) => {
+                    if matches!(kind, AlertKind::UsageWarning | AlertKind::UsageCritical) {
+                        self.usage_notifications.insert(target.id.clone(), notification_id);
+                    }
+                    self.notification_targets.insert(notification_id, target);
+                }
+                Err(why) => eprintln!("failed to send notification: {why}"),
+            },
+            Message::NotifierReady(connection) => {
+                self.notifier = Some(connection);
+            }
+            Message::NotificationEvent(notifications::Event::Closed(notification_id)) => {
+                self.notification_targets.remove(&notification_id);
+                self.usage_notifications.retain(|_, shown| *shown != notification_id);
+            }
+            Message::NotificationEvent(notifications::Event::Action(notification_id, action)) => {
+                // Ids that aren't ours belong to other applications' notifications
+                let Some(target) = self.notification_targets.remove(&notification_id) else {
+                    return Task::none();
+                };
+                match action.as_str() {
+                    "open" => {
+                        if let Some(mount_point) = target.mount_point {
+                            return self.update(Message::OpenFileManager(mount_point));
+                        }
+                    }
+                    "details" => {
+                        self.close_analyzer();
+                        self.focused_drive = Some(target.id);
+                        if self.popup.is_none() {
+                            return self.open_popup();
+                        }
+                    }
+                    "snooze" => {
+                        self.snoozed_until.insert(target.id, Instant::now() + SNOOZE_DURATION);
+                    }
+                    _ => {}
+                }
+            }
+            Message::ShowAllDrives => {
+                self.focused_drive = None;
+            }
+            Message::ServiceReady(connection) => {
+                self.service = Some(connection);
+            }
+            Message::ServiceRequest(service::Request::Refresh) => {
+                return self.refresh_mount_table();
+            }
+            Message::ServiceRequest(service::Request::Snooze(Some(id), until)) => {
+                self.snoozed_until.insert(id, until);
+            }
+            Message::ServiceRequest(service::Request::Snooze(None, until)) => {
+                self.all_snoozed_until = Some(until);
+            }
+            Message::ConfigChanged(config) => {
+                self.config = config;
+            }
+            Message::NotificationsConfigChanged(notifications_config) => {
+                self.do_not_disturb = notifications_config.do_not_disturb;
+            }
+        }
+        Task::none()
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        Subscription::batch([
+            time::every(Duration::from_secs(self.config.poll_interval)).map(|_| Message::Tick),
+            Subscription::run_with_id("udisks-mounts", mount_events()),
+            Subscription::run_with_id("dbus-service", service_events(self.service_drives.clone())),
+            Subscription::run_with_id("notification-actions", notification_events()),
+            self.core
+                .watch_config::<NotificationsConfig>(config::NOTIFICATIONS_CONFIG_ID)
+                .map(|update| Message::NotificationsConfigChanged(update.config)),
+        ])
+    }
+
+    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
+        Some(cosmic::applet::style())
+    }
+}
+
+impl CargoWatch {
+    /// Returns true if the given drive should be shown on the panel.
+    fn is_on_panel(&self, info: &DriveInfo) -> bool {
+        let mount_str = info.mount_point.display().to_string();
+        self.config.panel_drives.iter().any(|m| {
+            m == &info.id
+                || m == &mount_str
+                || (m == "/home" && mount_str.starts_with("/home"))
+        })
+    }
+
+    /// Opens the popup.
+    fn open_popup(&mut self) -> Task<Message> {
+        let new_id = Id::unique();
+        self.popup.replace(new_id);
+        let mut popup_settings = self.core.applet.get_popup_settings(
+            self.core.main_window_id().unwrap(),
+            new_id,
+            None,
+            None,
+            None,
+        );
+        popup_settings.positioner.size_limits = Limits::NONE
+            .max_width(372.0)
+            .min_width(300.0)
+            .min_height(100.0)
+            .max_height(600.0);
+        get_popup(popup_settings)
+    }
+
+    /// Copies the current drive status to the D-Bus service.
+    fn publish_drives(&self) {
+        let snapshots = self
+            .drives
+            .iter()
+            .map(|drive| DriveSnapshot {
+                id: drive.info.id.clone(),
+                mount_point: drive.info.mount_point.display().to_string(),
+                label: drive.info.display_name(),
+                device: drive.info.device.clone(),
+                fs_type: drive.info.fs_type.clone(),
+                total: drive.space.total,
+                used: drive.space.used,
+                available: drive.space.available,
+                percent: drive.space.percent_used(),
+            })
+            .collect();
+
+        *self.service_drives.lock().unwrap_or_else(PoisonError::into_inner) = snapshots;
+    }
+
+    /// Saves the current config to disk.
+    fn save_config(&self) {
+        if let Some(ref handler) = self.config_handler {
+            if let Err(why) = self.config.write_entry(handler) {
+                eprintln!("failed to save config: {why}");
+            }
+        }
+    }
+
+    /// Re-reads network mounts and ZFS datasets from the mount table on a
+    /// blocking worker. They replace the ones in the drive list when
+    /// [`Message::MountTableRead`] arrives, followed by a space refresh.
+    fn refresh_mount_table(&self) -> Task<Message> {
+        let include_network = self.config.monitor_network_mounts;
+
+        cosmic::task::future(async move {
+            let mounts = tokio::task::spawn_blocking(move || read_mount_table(include_network))
+                .await
+                .unwrap_or_else(|why| {
+                    eprintln!("mount table worker panicked: {why}");
+                    Vec::new()
+                });
+            Message::MountTableRead(mounts)
+        })
+    }
+
+    /// Returns the ZFS pool a dataset belongs to, if it has been listed.
+    fn pool_for(&self, info: &DriveInfo) -> Option<&PoolInfo> {
+        let name = zfs::pool_name(&info.device);
+        self.pools.iter().find(|pool| pool.name == name)
+    }
+
+    /// Returns true if a drive is a ZFS dataset whose pool has been listed,
+    /// so it is shown and alerted on through the pool. Without a pool list
+    /// (e.g. `zpool` missing or failing) datasets fall back to their own card.
+    fn in_listed_pool(&self, info: &DriveInfo) -> bool {
+        info.is_zfs() && self.pool_for(info).is_some()
+    }
+
+    /// Returns the usage percentage and alert level shown for a drive.
+    ///
+    /// ZFS datasets show their pool's capacity, since that is what fills up.
+    fn usage_level(&self, drive: &DriveStatus) -> (u8, AlertLevel) {
+        if let Some(pool) = self.pool_for(&drive.info).filter(|_| drive.info.is_zfs()) {
+            let space = pool.space();
+            let level = self.config.get_drive_alert(&pool.id()).level(&space);
+            return (space.percent_used(), level);
+        }
+
+        let level = self.config.get_drive_alert(&drive.info.id).level(&drive.space);
+        (drive.space.percent_used(), level)
+    }
+
+    /// Returns the alert settings that apply to a drive, which for ZFS
+    /// datasets are their pool's.
+    fn alert_config_for(&self, drive: &DriveStatus) -> DriveAlertConfig {
+        match self.pool_for(&drive.info).filter(|_| drive.info.is_zfs()) {
+            Some(pool) => self.config.get_drive_alert(&pool.id()),
+            None => self.config.get_drive_alert(&drive.info.id),
+        }
+    }
+
+    /// Writes the metrics file in the background, if the exporter is enabled.
+    fn write_metrics(&self) {
+        let Some(path) = &self.config.metrics_path else {
+            return;
+        };
+
+        let alert_configs: Vec<_> = self.drives.iter().map(|d| self.alert_config_for(d)).collect();
+        let drives: Vec<_> = self
+            .drives
+            .iter()
+            .zip(&alert_configs)
+            .map(|(drive, alert_config)| DriveMetrics {
+                mount_point: &drive.info.mount_point,
+                device: &drive.info.device,
+                fs_type: &drive.info.fs_type,
+                space: &drive.space,
+                warning_threshold: alert_config.warning_threshold,
+                critical_threshold: alert_config.critical_threshold,
+                alert_active: alert_config.enabled && self.usage_level(drive).1 >= AlertLevel::Warning,
+            })
+            .collect();
+
+        metrics::write_in_background(PathBuf::from(path), metrics::render(&drives));
+    }
+
+    /// Starts scanning `path` on the analyzer page, cancelling any running scan.
+    fn start_analysis(&mut self, root: PathBuf, path: PathBuf) -> Task<Message> {
+        self.close_analyzer();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.analyzer = Some(Analyzer {
+            root,
+            path: path.clone(),
+            cancel: cancel.clone(),
+            scanning: true,
+            result: None,
+            error: None,
+        });
+
+        cosmic::task::future(async move {
+            let result = analyzer::scan_async(path, cancel.clone())
+                .await
+                .map_err(|why| why.to_string());
+            Message::AnalysisFinished(cancel, result)
+        })
+    }
+
+    /// Closes the analyzer page and stops its scan.
+    fn close_analyzer(&mut self) {
+        if let Some(analyzer) = self.analyzer.take() {
+            analyzer.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Builds the analyzer page: the largest entries in the current directory,
+    /// with directories opening a deeper scan.
+    fn analyzer_view(&self, analyzer: &Analyzer) -> Element<'_, Message> {
+        // Back goes up one directory, and closes the page at the mount point
+        let back = analyzer
+            .path
+            .parent()
+            .filter(|_| analyzer.path != analyzer.root)
+            .map_or(Message::CloseAnalyzer, |parent| Message::AnalyzePath(parent.to_path_buf()));
+
+        let header_row = widget::row::Row::new()
+            .spacing(8)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(widget::button::icon(widget::icon::from_name("go-previous-symbolic")).on_press(back))
+            .push(text(analyzer.path.display().to_string()).size(14))
+            .push(widget::horizontal_space())
+            .push(widget::button::text(fl!("close")).on_press(Message::CloseAnalyzer));
+
+        let mut content = widget::column::Column::new()
+            .spacing(8)
+            .padding(12)
+            .push(header_row);
+
+        if analyzer.scanning {
+            content = content.push(
+                widget::row::Row::new()
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(text(fl!("scanning")).size(12))
+                    .push(widget::horizontal_space())
+                    .push(widget::button::text(fl!("cancel")).on_press(Message::CancelAnalysis)),
+            );
+        } else if let Some(why) = &analyzer.error {
+            content = content.push(
+                text(why.clone())
+                    .size(12)
+                    .class(theme::Text::Custom(danger_text_style)),
+            );
+        } else if let Some(result) = &analyzer.result {
+            content = content.push(
+                text(fl!("analyzer-total", size = space::format_bytes(result.total))).size(12),
+            );
+
+            let mut list = widget::column::Column::new().spacing(6);
+            for entry in &result.entries {
+                let name = entry
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| entry.path.display().to_string());
+                let fraction = if result.total == 0 {
+                    0.0
+                } else {
+                    entry.size as f32 / result.total as f32 * 100.0
+                };
+
+                // Directories drill down; files can only be opened
+                let name: Element<Message> = if entry.is_dir {
+                    widget::button::text(format!("{name}/"))
+                        .class(theme::Button::Link)
+                        .on_press(Message::AnalyzePath(entry.path.clone()))
+                        .into()
+                } else {
+                    text(name).size(12).into()
+                };
+
+                let row = widget::row::Row::new()
+                    .spacing(8)
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(name)
+                    .push(widget::horizontal_space())
+                    .push(text(space::format_bytes(entry.size)).size(12))
+                    .push(
+                        widget::button::icon(widget::icon::from_name("document-open-symbolic"))
+                            .on_press(Message::OpenFileManager(entry.path.clone())),
+                    );
+
+                list = list.push(
+                    widget::column::Column::new()
+                        .spacing(2)
+                        .push(row)
+                        .push(widget::progress_bar(0.0..=100.0, fraction).height(4)),
+                );
+            }
+
+            content = content.push(widget::scrollable(list).height(Length::Fixed(420.0)));
+        } else {
+            // Cancelled before it finished
+            content = content.push(
+                widget::row::Row::new()
+                    .align_y(cosmic::iced::Alignment::Center)
+                    .push(text(fl!("scan-cancelled")).size(12))
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::text(fl!("rescan"))
+                            .on_press(Message::AnalyzePath(analyzer.path.clone())),
+                    ),
+            );
+        }
+
+        content.into()
+    }
+
+    /// Builds the "Reclaim space" card: a scan button, then each reclaimable
+    /// item with its size and a clean button that asks for confirmation.
+    fn reclaim_card(&self) -> Element<'_, Message> {
+        let reclaim = &self.reclaim;
+
+        let find = widget::button::text(if reclaim.items.is_some() { fl!("rescan") } else { fl!("reclaim-find") })
+            .on_press_maybe((!reclaim.scanning).then_some(Message::FindReclaimable));
+
+        let mut list = widget::column::Column::new().spacing(6).push(
+            widget::row::Row::new()
+                .align_y(cosmic::iced::Alignment::Center)
+                .push(text(fl!("reclaim-space")).size(14))
+                .push(widget::horizontal_space())
+                .push(find),
+        );
+
+        if reclaim.scanning {
+            list = list.push(text(fl!("scanning")).size(12));
+        } else if reclaim.items.as_ref().is_some_and(|items| items.is_empty()) {
+            list = list.push(text(fl!("reclaim-nothing")).size(12));
+        }
+
+        for item in reclaim.items.iter().flatten() {
+            let kind = item.kind;
+
+            let action: Element<Message> = if reclaim.cleaning.contains(&kind) {
+                text(fl!("cleaning")).size(12).into()
+            } else if let Some(Ok(freed)) = reclaim.results.get(&kind) {
+                text(fl!("reclaim-freed", size = space::format_bytes(*freed)))
+                    .size(12)
+                    .class(theme::Text::Custom(success_text_style))
+                    .into()
+            } else if reclaim.confirming == Some(kind) {
+                widget::row::Row::new()
+                    .spacing(4)
+                    .push(
+                        widget::button::text(fl!("confirm"))
+                            .class(theme::Button::Destructive)
+                            .on_press(Message::RunCleanup(kind)),
+                    )
+                    .push(
+                        widget::button::text(fl!("cancel"))
+                            .on_press(Message::ConfirmCleanup(None)),
+                    )
+                    .into()
+            } else {
+                widget::button::text(fl!("clean"))
+                    .on_press(Message::ConfirmCleanup(Some(kind)))
+                    .into()
+            };
+
+            let row = widget::row::Row::new()
+                .spacing(8)
+                .align_y(cosmic::iced::Alignment::Center)
+                .push(
+                    widget::column::Column::new()
+                        .push(text(cleanup_label(kind)).size(13))
+                        .push(text(space::format_bytes(item.size)).size(11)),
+                )
+                .push(widget::horizontal_space())
+                .push(action);
+            list = list.push(row);
+
+            if let Some(Err(why)) = reclaim.results.get(&kind) {
+                list = list.push(
+                    text(why.clone())
+                        .size(11)
+                        .class(theme::Text::Custom(danger_text_style)),
+                );
+            }
+        }
+
+        container(list)
+            .padding(8)
+            .width(Length::Fill)
+            .class(theme::Container::Card)
+            .into()
+    }
+
+    /// Builds the card for a ZFS pool: pool capacity, health and thresholds,
+    /// with each mounted dataset's own usage listed.
+    fn pool_card(&self, pool: &PoolInfo, datasets: &[&DriveStatus]) -> Element<'_, Message> {
+        let id = pool.id();
+        let space = pool.space();
+        let pct = space.percent_used();
+        let alert_config = self.config.get_drive_alert(&id);
+        let level = alert_config.level(&space);
+
+        let id_alert = id.clone();
+        let id_warning = id.clone();
+        let id_critical = id;
+
+        let header_row = widget::row::Row::new()
+            .spacing(6)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(widget::icon::from_name("drive-multidisk-symbolic").size(16).icon())
+            .push(text(pool.name.clone()).size(14))
+            .push(widget::horizontal_space())
+            .push(
+                text(format!(
+                    "{} / {}",
+                    space::format_bytes(pool.allocated),
+                    space::format_bytes(pool.size)
+                ))
+                .size(12),
+            );
+
+        let bar = widget::progress_bar(0.0..=100.0, pct as f32).height(8);
+        let bar_widget: Element<Message> = match level {
+            AlertLevel::Critical => bar.class(theme::ProgressBar::Danger).into(),
+            AlertLevel::Warning => bar.class(theme::ProgressBar::Custom(Box::new(warning_bar_style))).into(),
+            AlertLevel::Normal => bar.into(),
+        };
+
+        let health = if pool.is_healthy() {
+            text(pool.health.clone())
+        } else {
+            text(pool.health.clone()).class(theme::Text::Custom(danger_text_style))
+        };
+
+        let mut footer_row = widget::row::Row::new().spacing(8).push(health.size(11));
+        if let Some(fragmentation) = pool.fragmentation {
+            footer_row = footer_row
+                .push(text(fl!("zfs-fragmentation", percent = fragmentation.to_string())).size(11));
+        }
+        let footer_row = footer_row
+            .push(widget::horizontal_space())
+            .push(text(format!("{pct}%")).size(12));
+
+        let mut dataset_list = widget::column::Column::new().spacing(2);
+        for dataset in datasets {
+            let row = widget::row::Row::new()
+                .spacing(8)
+                .push(text(dataset.info.mount_point.display().to_string()).size(11))
+                .push(widget::horizontal_space())
+                .push(
+                    text(format!(
+                        "{} · {}",
+                        dataset.info.device,
+                        space::format_bytes(dataset.space.used)
+                    ))
+                    .size(11),
+                );
+            dataset_list = dataset_list.push(
+                widget::mouse_area(row).on_press(Message::OpenFileManager(dataset.info.mount_point.clone())),
+            );
+        }
 
-            let state = self.alert_states.entry(path.clone()).or_insert(AlertState {
-                last_alerted: Instant::now() - cooldown - Duration::from_secs(1),
-                was_over_threshold: false,
+        let alert_toggle = widget::checkbox(fl!("enable-alerts"), alert_config.enabled)
+            .on_toggle(move |checked| Message::ToggleDriveAlert(id_alert.clone(), checked))
+            .size(14);
+
+        let warning_row = widget::row::Row::new()
+            .spacing(8)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(text(fl!("warning-threshold")).size(12))
+            .push(
+                widget::slider(50..=99, alert_config.warning_threshold, move |val| {
+                    Message::SetDriveWarningThreshold(id_warning.clone(), val)
+                })
+                .width(Length::Fixed(100.0)),
+            )
+            .push(text(format!("{}%", alert_config.warning_threshold)).size(12));
+
+        let critical_row = widget::row::Row::new()
+            .spacing(8)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(text(fl!("critical-threshold")).size(12))
+            .push(
+                widget::slider(50..=99, alert_config.critical_threshold, move |val| {
+                    Message::SetDriveCriticalThreshold(id_critical.clone(), val)
+                })
+                .width(Length::Fixed(100.0)),
+            )
+            .push(text(format!("{}%", alert_config.critical_threshold)).size(12));
+
+        let card_content = widget::column::Column::new()
+            .spacing(6)
+            .push(header_row)
+            .push(bar_widget)
+            .push(footer_row)
+            .push(dataset_list)
+            .push(widget::divider::horizontal::light())
+            .push(alert_toggle)
+            .push(warning_row)
+            .push(critical_row);
+
+        container(card_content)
+            .padding(8)
+            .width(Length::Fill)
+            .class(theme::Container::Card)
+            .into()
+    }
+
+    /// Starts an unmount, eject or power off for the drive with the given id.
+    fn run_drive_action(&mut self, id: String, action: DriveAction) -> Task<Message> {
+        let Some(info) = self.mounted.iter().find(|d| d.id == id) else {
+            return Task::none();
+        };
+        let Some(object_path) = info.object_path.clone() else {
+            return Task::none();
+        };
+        let drive_path = info.drive_path.clone();
+
+        self.action_errors.remove(&id);
+        self.pending_actions.insert(id.clone());
+
+        cosmic::task::future(async move {
+            let result = match (action, drive_path) {
+                (DriveAction::Unmount, _) => udisks::unmount(object_path).await,
+                (DriveAction::Eject, Some(drive_path)) => udisks::eject(object_path, drive_path).await,
+                (DriveAction::PowerOff, Some(drive_path)) => {
+                    udisks::power_off(object_path, drive_path).await
+                }
+                (_, None) => Err(anyhow::anyhow!("no drive to eject")),
+            };
+            Message::DriveActionFinished(id, result.map_err(|why| why.to_string()))
+        })
+    }
+
+    /// Starts a background refresh of space info for the currently mounted drives.
+    ///
+    /// Each statvfs runs on a blocking worker; the results arrive as
+    /// [`Message::DrivesRefreshed`].
+    fn refresh_space(&self) -> Task<Message> {
+        let filtered: Vec<_> = self
+            .mounted
+            .iter()
+            .filter(|d| self.config.monitors(d))
+            .cloned()
+            .collect();
+
+        // Last known values, kept for drives whose statvfs hangs
+        let last_known: HashMap<PathBuf, SpaceInfo> = self
+            .drives
+            .iter()
+            .map(|d| (d.info.mount_point.clone(), d.space.clone()))
+            .collect();
+        let timeout = Duration::from_secs(self.config.statvfs_timeout);
+        let has_zfs = filtered.iter().any(|info| info.is_zfs());
+        let history = self.history.clone();
+        let graph_range = self.graph_range.duration();
+
+        cosmic::task::future(async move {
+            let queries = filtered.into_iter().map(|info| {
+                let last = last_known.get(&info.mount_point).cloned();
+                query_drive(info, last, timeout)
             });
 
-            // Alert if:
-            // 1. Currently over threshold AND
-            // 2. Either just crossed threshold OR cooldown expired
-            let crossed_threshold = over_threshold && !state.was_over_threshold;
-            let cooldown_expired = now.duration_since(state.last_alerted) >= cooldown;
+            let mut drives: Vec<_> = futures_util::future::join_all(queries)
+                .await
+                .into_iter()
+                .flatten()
+                .collect();
+            record_history(history, &mut drives, graph_range).await;
+
+            let pools = if has_zfs {
+                match zfs::list_pools_async(timeout).await {
+                    Ok(Some(pools)) => Some(pools),
+                    Ok(None) => {
+                        eprintln!("zpool list timed out or is still running");
+                        None
+                    }
+                    Err(why) => {
+                        eprintln!("failed to list ZFS pools: {why}");
+                        None
+                    }
+                }
+            } else {
+                Some(Vec::new())
+            };
 
-            if over_threshold && (crossed_threshold || cooldown_expired) {
-                alerts_to_send.push((drive.info.display_name(), pct));
-                state.last_alerted = now;
+            Message::DrivesRefreshed(drives, pools)
+        })
+    }
+
+    /// Checks drives against alert thresholds and sends notifications.
+    ///
+    /// Returns a task per notification that waits for its action buttons.
+    fn check_alerts(&mut self) -> Task<Message> {
+        use notify_rust::Urgency;
+
+        let now = Instant::now();
+        let cooldown = Duration::from_secs(self.config.alert_cooldown);
+
+        // Collect alerts to send (avoids borrow conflict)
+        let mut alerts_to_send: Vec<(AlertTarget, (PathBuf, AlertKind), String, String, Urgency)> = Vec::new();
+        // Drives and pools whose usage dropped back below the warning level
+        let mut recovered: Vec<(AlertTarget, String)> = Vec::new();
+        // Usage level changes, published as the ThresholdCrossed D-Bus signal
+        let mut crossings: Vec<(String, String, AlertLevel, u8)> = Vec::new();
+        // User hook commands to run, with the event and drive details
+        let mut hook_runs: Vec<(String, HookEvent, HookContext)> = Vec::new();
+        let margin = self.config.hysteresis_margin;
+
+        for drive in &self.drives {
+            let path = &drive.info.mount_point;
+            let target = AlertTarget {
+                id: drive.info.id.clone(),
+                mount_point: Some(path.clone()),
+            };
+            let alert_config = self.config.get_drive_alert(&drive.info.id);
+
+            // Skip if alerts disabled, or the values are stale. ZFS datasets
+            // of a listed pool alert through the pool below.
+            if !alert_config.enabled || drive.unresponsive || self.in_listed_pool(&drive.info) {
+                continue;
+            }
+
+            let name = drive.info.display_name();
+            let pct = drive.space.percent_used();
+            let previous = alerted_level(&self.alert_states, path);
+            let held = alert_config.held_level(&drive.space, margin);
+            let level = alert_config.level(&drive.space).max(previous.min(held));
+
+            let body = if alert_config.below_min_free(drive.space.available) {
+                let free = space::format_bytes(drive.space.available);
+                fl!("alert-free-body", drive = name.as_str(), free = free)
+            } else {
+                fl!("alert-body", drive = name.as_str(), percent = pct.to_string())
+            };
+
+            let hook_context = HookContext {
+                drive: name.clone(),
+                mount_point: Some(path.clone()),
+                level,
+                percent: pct,
+                free: drive.space.available,
+            };
+
+            // Each level alerts on its own, so warning -> critical notifies again
+            for (kind, alert_level, title, urgency) in [
+                (AlertKind::UsageWarning, AlertLevel::Warning, fl!("alert-title"), Urgency::Normal),
+                (AlertKind::UsageCritical, AlertLevel::Critical, fl!("alert-critical-title"), Urgency::Critical),
+            ] {
+                let state_key = (path.clone(), kind);
+                if should_alert(&mut self.alert_states, state_key.clone(), level == alert_level, now, cooldown) {
+                    alerts_to_send.push((target.clone(), state_key, title, body.clone(), urgency));
+                    hook_runs.extend(hook_run(&alert_config, previous, alert_level, &hook_context));
+                }
             }
 
-            state.was_over_threshold = over_threshold;
+            if previous > AlertLevel::Normal && level == AlertLevel::Normal {
+                recovered.push((target.clone(), name.clone()));
+                hook_runs.extend(hook_run(&alert_config, previous, level, &hook_context));
+            }
+            if level != previous {
+                crossings.push((target.id.clone(), path.display().to_string(), level, pct));
+            }
+
+            let inode_pct = drive.space.inodes_percent_used();
+            let inode_level = if drive.space.has_inodes() {
+                alert_config.inode_level(inode_pct)
+            } else {
+                AlertLevel::Normal
+            };
+
+            let state_key = (path.clone(), AlertKind::Inodes);
+            if should_alert(
+                &mut self.alert_states,
+                state_key.clone(),
+                inode_level >= AlertLevel::Warning,
+                now,
+                cooldown,
+            ) {
+                let (title, urgency) = if inode_level == AlertLevel::Critical {
+                    (fl!("alert-critical-title"), Urgency::Critical)
+                } else {
+                    (fl!("alert-title"), Urgency::Normal)
+                };
+                let body = fl!("alert-inode-body", drive = name.as_str(), percent = inode_pct.to_string());
+                alerts_to_send.push((target.clone(), state_key, title, body, urgency));
+            }
+
+            // Quota is per filesystem, so key by device to alert once for / and /home
+            let quota_pct = drive.quota.as_ref().map_or(0, QuotaInfo::percent_used);
+            let quota_level = drive
+                .quota
+                .as_ref()
+                .map_or(AlertLevel::Normal, |_| alert_config.quota_level(quota_pct));
+            for (kind, alert_level, title, urgency) in [
+                (AlertKind::Quota, AlertLevel::Warning, fl!("alert-title"), Urgency::Normal),
+                (AlertKind::QuotaExceeded, AlertLevel::Critical, fl!("alert-critical-title"), Urgency::Critical),
+            ] {
+                let state_key = (PathBuf::from(&drive.info.device), kind);
+                if should_alert(&mut self.alert_states, state_key.clone(), quota_level == alert_level, now, cooldown) {
+                    let body = fl!("alert-quota-body", drive = name.as_str(), percent = quota_pct.to_string());
+                    alerts_to_send.push((target.clone(), state_key, title, body, urgency));
+                }
+            }
+
+            // Btrfs metadata, keyed by device so / and /home on one filesystem alert once
+            if let Some(btrfs) = &drive.btrfs {
+                let metadata_pct = btrfs.metadata_percent_used();
+                let metadata_level = alert_config.metadata_level(metadata_pct);
+
+                let state_key = (PathBuf::from(&drive.info.device), AlertKind::BtrfsMetadata);
+                if should_alert(
+                    &mut self.alert_states,
+                    state_key.clone(),
+                    metadata_level >= AlertLevel::Warning,
+                    now,
+                    cooldown,
+                ) {
+                    let (title, urgency) = if metadata_level == AlertLevel::Critical {
+                        (fl!("alert-critical-title"), Urgency::Critical)
+                    } else {
+                        (fl!("alert-title"), Urgency::Normal)
+                    };
+                    let body = fl!(
+                        "alert-btrfs-metadata-body",
+                        drive = name.as_str(),
+                        percent = metadata_pct.to_string()
+                    );
+                    alerts_to_send.push((target.clone(), state_key, title, body, urgency));
+                }
+            }
+
+            // Health alerts are keyed by the physical drive so partitions don't repeat them
+            if let (Some(health), Some(drive_path)) = (&drive.info.health, &drive.info.drive_path) {
+                let drive_key = PathBuf::from(drive_path.as_str());
+                let model = drive.info.model.clone().unwrap_or_else(|| name.clone());
+
+                let state_key = (drive_key.clone(), AlertKind::SmartFailing);
+                if should_alert(&mut self.alert_states, state_key.clone(), health.failing, now, cooldown) {
+                    let body = fl!("alert-smart-body", drive = model.as_str());
+                    alerts_to_send.push((
+                        target.clone(),
+                        state_key,
+                        fl!("alert-health-title"),
+                        body,
+                        Urgency::Critical,
+                    ));
+                }
+
+                let limit = f64::from(self.config.temperature_limit);
+                let too_hot = health.temperature.is_some_and(|celsius| celsius > limit);
+                let state_key = (drive_key, AlertKind::Temperature);
+                if should_alert(&mut self.alert_states, state_key.clone(), too_hot, now, cooldown) {
+                    let celsius = format!("{:.0}", health.temperature.unwrap_or_default());
+                    let body = fl!("alert-temperature-body", drive = model.as_str(), temperature = celsius);
+                    alerts_to_send.push((
+                        target.clone(),
+                        state_key,
+                        fl!("alert-health-title"),
+                        body,
+                        Urgency::Normal,
+                    ));
+                }
+            }
+
+            let predicted_full = alert_config
+                .predict_full_hours
+                .zip(drive.time_to_full)
+                .filter(|(hours, eta)| *eta <= Duration::from_secs(u64::from(*hours) * 3600))
+                .map(|(_, eta)| eta);
+
+            let state_key = (path.clone(), AlertKind::PredictedFull);
+            if should_alert(&mut self.alert_states, state_key.clone(), predicted_full.is_some(), now, cooldown) {
+                let eta = format_duration(predicted_full.unwrap_or_default());
+                let body = fl!("alert-predicted-body", drive = name.as_str(), time = eta);
+                alerts_to_send.push((
+                    target,
+                    state_key,
+                    fl!("alert-title"),
+                    body,
+                    Urgency::Normal,
+                ));
+            }
+        }
+
+        // ZFS pools with a monitored dataset, alerted once per pool
+        let monitored_pools: HashSet<&str> = self
+            .drives
+            .iter()
+            .filter(|d| d.info.is_zfs())
+            .map(|d| zfs::pool_name(&d.info.device))
+            .collect();
+
+        for pool in self.pools.iter().filter(|pool| monitored_pools.contains(pool.name.as_str())) {
+            let id = pool.id();
+            let alert_config = self.config.get_drive_alert(&id);
+            if !alert_config.enabled {
+                continue;
+            }
+
+            let key = PathBuf::from(&id);
+            let target = AlertTarget {
+                id,
+                mount_point: None,
+            };
+            let space = pool.space();
+            let previous = alerted_level(&self.alert_states, &key);
+            let held = alert_config.held_level(&space, margin);
+            let level = alert_config.level(&space).max(previous.min(held));
+
+            let body = if alert_config.below_min_free(pool.free) {
+                let free = space::format_bytes(pool.free);
+                fl!("alert-free-body", drive = pool.name.as_str(), free = free)
+            } else {
+                let pct = space.percent_used().to_string();
+                fl!("alert-body", drive = pool.name.as_str(), percent = pct)
+            };
+
+            let hook_context = HookContext {
+                drive: pool.name.clone(),
+                mount_point: None,
+                level,
+                percent: space.percent_used(),
+                free: pool.free,
+            };
+
+            for (kind, alert_level, title, urgency) in [
+                (AlertKind::UsageWarning, AlertLevel::Warning, fl!("alert-title"), Urgency::Normal),
+                (AlertKind::UsageCritical, AlertLevel::Critical, fl!("alert-critical-title"), Urgency::Critical),
+            ] {
+                let state_key = (key.clone(), kind);
+                if should_alert(&mut self.alert_states, state_key.clone(), level == alert_level, now, cooldown) {
+                    alerts_to_send.push((target.clone(), state_key, title, body.clone(), urgency));
+                    hook_runs.extend(hook_run(&alert_config, previous, alert_level, &hook_context));
+                }
+            }
+
+            if previous > AlertLevel::Normal && level == AlertLevel::Normal {
+                recovered.push((target.clone(), pool.name.clone()));
+                hook_runs.extend(hook_run(&alert_config, previous, level, &hook_context));
+            }
+            if level != previous {
+                crossings.push((target.id.clone(), String::new(), level, space.percent_used()));
+            }
+
+            let state_key = (key, AlertKind::PoolHealth);
+            if should_alert(&mut self.alert_states, state_key.clone(), !pool.is_healthy(), now, cooldown) {
+                let body = fl!("alert-pool-health-body", pool = pool.name.as_str(), health = pool.health.as_str());
+                alerts_to_send.push((
+                    target,
+                    state_key,
+                    fl!("alert-critical-title"),
+                    body,
+                    Urgency::Critical,
+                ));
+            }
+        }
+
+        // Alert states above still advance while snoozed, so nothing is
+        // queued up for when the snooze ends
+        self.snoozed_until.retain(|_, until| *until > now);
+        self.all_snoozed_until = self.all_snoozed_until.filter(|until| *until > now);
+        let snoozed = |id: &String| self.all_snoozed_until.is_some() || self.snoozed_until.contains_key(id);
+
+        let paused = self.alerts_paused_until.is_some_and(|until| until > now);
+        if !paused {
+            self.alerts_paused_until = None;
+        }
+        let quiet = self.in_quiet_hours();
+
+        let mut tasks = Vec::new();
+        for (target, state_key, summary, body, urgency) in alerts_to_send {
+            if paused || snoozed(&target.id) {
+                continue;
+            }
+            // Critical alerts still get through quiet hours
+            if quiet && !matches!(urgency, Urgency::Critical) {
+                self.quiet_queue.push(state_key, body);
+                continue;
+            }
+            tasks.push(self.send_alert(target, state_key.1, summary, body, urgency));
+        }
+
+        for (target, name) in recovered {
+            let replaces = self.usage_notifications.remove(&target.id);
+            if self.config.notify_recovery && !paused && !quiet && !snoozed(&target.id) {
+                Self::send_recovery(&name, replaces);
+            }
+        }
+
+        self.quiet_queue.retain_active(&self.alert_states);
+
+        // Quiet hours are over: deliver what was held back as one notification
+        if !quiet && !paused && !self.quiet_queue.is_empty() {
+            Self::send_quiet_summary(&self.quiet_queue.take());
+        }
+
+        // Hooks are automation rather than notifications, so they run even while snoozed
+        let hook_timeout = Duration::from_secs(self.config.hook_timeout);
+        for (command, event, context) in hook_runs {
+            hooks::run(command, event, context, hook_timeout);
         }
 
-        for (name, pct) in alerts_to_send {
-            Self::send_alert(&name, pct);
+        if let Some(connection) = &self.service {
+            for (id, mount_point, level, percent) in crossings {
+                service::emit_threshold_crossed(connection, id, mount_point, level.as_str(), percent);
+            }
         }
+
+        Task::batch(tasks)
     }
 
-    fn send_alert(name: &str, pct: u8) {
-        use notify_rust::{Notification, Urgency};
+    /// Returns true during the configured quiet hours or while COSMIC's Do
+    /// Not Disturb mode is on.
+    fn in_quiet_hours(&self) -> bool {
+        let in_window = self
+            .config
+            .quiet_hours
+            .as_ref()
+            .zip(localtime::minute_of_day())
+            .is_some_and(|(quiet_hours, minute)| quiet_hours.contains(minute));
+
+        in_window || self.do_not_disturb
+    }
 
-        let summary = fl!("alert-title");
-        let body = fl!("alert-body", drive = name, percent = pct.to_string());
+    /// Shows the alerts held back during quiet hours as one notification.
+    fn send_quiet_summary(bodies: &[String]) {
+        use notify_rust::{Notification, Urgency};
 
         if let Err(why) = Notification::new()
-            .summary(&summary)
-            .body(&body)
+            .summary(&fl!("alert-quiet-summary-title", count = bodies.len()))
+            .body(&bodies.join("\n"))
             .icon("drive-harddisk")
-            .urgency(Urgency::Critical)
+            .urgency(Urgency::Normal)
             .show()
         {
             eprintln!("failed to send notification: {why}");
         }
     }
+
+    /// Shows a low-urgency "Disk space recovered" notification, replacing
+    /// the drive's last usage alert if it is still on screen.
+    fn send_recovery(name: &str, replaces: Option<u32>) {
+        use notify_rust::{Notification, Urgency};
+
+        let mut notification = Notification::new();
+        notification
+            .summary(&fl!("alert-recovered-title"))
+            .body(&fl!("alert-recovered-body", drive = name))
+            .icon("drive-harddisk")
+            .urgency(Urgency::Low);
+        if let Some(id) = replaces {
+            notification.id(id);
+        }
+
+        if let Err(why) = notification.show() {
+            eprintln!("failed to send notification: {why}");
+        }
+    }
+
+    /// Shows a notification with Open, Show details and Snooze buttons.
+    ///
+    /// The task reports the notification id as [`Message::AlertShown`];
+    /// button presses arrive later from [`notification_events`].
+    fn send_alert(
+        &self,
+        target: AlertTarget,
+        kind: AlertKind,
+        summary: String,
+        body: String,
+        urgency: notify_rust::Urgency,
+    ) -> Task<Message> {
+        let Some(connection) = self.notifier.clone() else {
+            // Nothing is listening for button presses yet, so leave them out
+            if let Err(why) = notify_rust::Notification::new()
+                .summary(&summary)
+                .body(&body)
+                .icon("drive-harddisk")
+                .urgency(urgency)
+                .show()
+            {
+                eprintln!("failed to send notification: {why}");
+            }
+            return Task::none();
+        };
+
+        let mut actions = Vec::new();
+        if target.mount_point.is_some() {
+            actions.push(("open", fl!("notification-open")));
+        }
+        actions.push(("details", fl!("notification-details")));
+        actions.push(("snooze", fl!("notification-snooze")));
+
+        cosmic::task::future(async move {
+            let result = notifications::show(&connection, &summary, &body, urgency, &actions)
+                .await
+                .map_err(|why| format!("{why:#}"));
+            Message::AlertShown(target, kind, result)
+        })
+    }
+}
+
+/// Returns the hook command to run when a drive moves from `previous` to
+/// `level`, with its event, if the drive has one configured.
+fn hook_run(
+    alert_config: &DriveAlertConfig,
+    previous: AlertLevel,
+    level: AlertLevel,
+    context: &HookContext,
+) -> Option<(String, HookEvent, HookContext)> {
+    let event = if level == AlertLevel::Normal {
+        HookEvent::Recovered
+    } else if level > previous {
+        HookEvent::Crossed
+    } else {
+        // Re-alert after the cooldown, or dropped from critical to warning
+        HookEvent::StillOver
+    };
+
+    let command = alert_config.hooks.command(event)?;
+    Some((command.to_string(), event, context.clone()))
+}
+
+/// Returns the usage level a drive or pool is currently alerted at.
+fn alerted_level(states: &HashMap<(PathBuf, AlertKind), AlertState>, key: &Path) -> AlertLevel {
+    let over = |kind| {
+        states
+            .get(&(key.to_path_buf(), kind))
+            .is_some_and(|state| state.was_over_threshold)
+    };
+
+    if over(AlertKind::UsageCritical) {
+        AlertLevel::Critical
+    } else if over(AlertKind::UsageWarning) {
+        AlertLevel::Warning
+    } else {
+        AlertLevel::Normal
+    }
+}
+
+/// Updates the alert state for `key` and returns true if a notification is due.
+///
+/// A notification is due when the condition is active and either just became
+/// active or the cooldown since the last notification has expired.
+fn should_alert(
+    states: &mut HashMap<(PathBuf, AlertKind), AlertState>,
+    key: (PathBuf, AlertKind),
+    active: bool,
+    now: Instant,
+    cooldown: Duration,
+) -> bool {
+    let state = states.entry(key).or_insert(AlertState {
+        last_alerted: now - cooldown - Duration::from_secs(1),
+        was_over_threshold: false,
+    });
+
+    let crossed_threshold = active && !state.was_over_threshold;
+    let cooldown_expired = now.duration_since(state.last_alerted) >= cooldown;
+    let due = active && (crossed_threshold || cooldown_expired);
+
+    if due {
+        state.last_alerted = now;
+    }
+    state.was_over_threshold = active;
+
+    due
+}
+
+/// Number of columns in a drive card's usage graph.
+const GRAPH_BUCKETS: u64 = 60;
+/// Height in pixels of a drive card's usage graph.
+const GRAPH_HEIGHT: f32 = 24.0;
+
+/// Groups samples from the last `range` into [`GRAPH_BUCKETS`] columns,
+/// each holding the peak usage percentage in its slice of time.
+fn graph_peaks(samples: &[Sample], range: Duration) -> Vec<Option<u8>> {
+    let now = history::unix_now();
+    let start = now.saturating_sub(range.as_secs());
+    let bucket_len = (range.as_secs() / GRAPH_BUCKETS).max(1);
+
+    let mut peaks = vec![None::<u8>; GRAPH_BUCKETS as usize];
+    for sample in samples.iter().filter(|s| s.total > 0 && s.timestamp >= start) {
+        let index = ((sample.timestamp - start) / bucket_len).min(GRAPH_BUCKETS - 1) as usize;
+        let pct = ((sample.used as f64 / sample.total as f64) * 100.0).round() as u8;
+        peaks[index] = Some(peaks[index].map_or(pct, |peak| peak.max(pct)));
+    }
+
+    peaks
+}
+
+/// Draws a compact bar sparkline from [`graph_peaks`], colored by the
+/// drive's alert level.
+fn usage_graph<'a>(peaks: &[Option<u8>], alert_config: &DriveAlertConfig) -> Element<'a, Message> {
+    let mut row = widget::row::Row::new()
+        .spacing(1)
+        .height(Length::Fixed(GRAPH_HEIGHT))
+        .align_y(cosmic::iced::Alignment::End);
+
+    for &peak in peaks {
+        let column = container(widget::horizontal_space()).width(Length::Fill);
+        let column = match peak {
+            Some(pct) => {
+                let style: fn(&Theme) -> cosmic::iced_widget::container::Style = match alert_config.percent_level(pct) {
+                    AlertLevel::Critical => graph_danger_style,
+                    AlertLevel::Warning => graph_warning_style,
+                    AlertLevel::Normal => graph_bar_style,
+                };
+                column
+                    .height(Length::Fixed((GRAPH_HEIGHT * f32::from(pct) / 100.0).max(1.0)))
+                    .class(theme::Container::Custom(Box::new(style)))
+            }
+            None => column.height(Length::Fixed(0.0)),
+        };
+        row = row.push(column);
+    }
+
+    row.into()
+}
+
+/// Formats a duration as a rough, localized estimate (e.g. "~3 days").
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+
+    if days >= 1 {
+        fl!("time-days", count = days)
+    } else if hours >= 1 {
+        fl!("time-hours", count = hours)
+    } else {
+        fl!("time-minutes", count = minutes.max(1))
+    }
+}
+
+/// Lists ZFS datasets, and network mounts if `include_network` is set,
+/// from the mount table.
+fn read_mount_table(include_network: bool) -> Vec<DriveInfo> {
+    let mut mounts = Vec::new();
+
+    if include_network {
+        match mountinfo::network_mounts() {
+            Ok(network) => mounts.extend(network),
+            Err(why) => eprintln!("failed to enumerate network mounts: {why}"),
+        }
+    }
+
+    match mountinfo::zfs_datasets() {
+        Ok(datasets) => mounts.extend(datasets),
+        Err(why) => eprintln!("failed to enumerate ZFS datasets: {why}"),
+    }
+
+    mounts
+}
+
+/// Reads space info for one drive, falling back to `last` if statvfs times out.
+async fn query_drive(info: DriveInfo, last: Option<SpaceInfo>, timeout: Duration) -> Option<DriveStatus> {
+    match space::get_space_info_async(info.mount_point.clone(), timeout).await {
+        Ok(Some(space)) => {
+            let btrfs = read_btrfs_usage(&info);
+            let quota = read_quota(&info, timeout).await;
+            Some(DriveStatus {
+                info,
+                space,
+                unresponsive: false,
+                time_to_full: None,
+                graph: Vec::new(),
+                btrfs,
+                quota,
+            })
+        }
+        Ok(None) => {
+            eprintln!("statvfs timed out for {}", info.mount_point.display());
+            Some(DriveStatus {
+                info,
+                space: last.unwrap_or_default(),
+                unresponsive: true,
+                time_to_full: None,
+                graph: Vec::new(),
+                btrfs: None,
+                quota: None,
+            })
+        }
+        Err(why) => {
+            eprintln!(
+                "failed to get space for {}: {why}",
+                info.mount_point.display()
+            );
+            None
+        }
+    }
+}
+
+/// Records a usage sample for each responsive drive, then fills in each
+/// drive's predicted time to full and usage graph over `graph_range`.
+///
+/// Runs on a blocking worker since samples are appended to disk.
+async fn record_history(history: Arc<Mutex<HistoryStore>>, drives: &mut [DriveStatus], graph_range: Duration) {
+    let usage: Vec<_> = drives
+        .iter()
+        .map(|d| (d.info.mount_point.clone(), (!d.unresponsive).then(|| d.space.clone())))
+        .collect();
+
+    let derived = tokio::task::spawn_blocking(move || {
+        let mut history = history.lock().unwrap_or_else(PoisonError::into_inner);
+        usage
+            .into_iter()
+            .map(|(mount, space)| {
+                // Unresponsive drives keep their graph but get no new sample or prediction
+                let time_to_full = space.and_then(|space| {
+                    if let Err(why) = history.record(&mount, &space) {
+                        eprintln!("failed to record usage history: {why}");
+                    }
+                    history.time_to_full(&mount, space.available)
+                });
+                let graph = graph_peaks(&history.recent(&mount, graph_range), graph_range);
+                (mount, (time_to_full, graph))
+            })
+            .collect::<HashMap<_, _>>()
+    })
+    .await;
+
+    match derived {
+        Ok(mut derived) => {
+            for drive in drives {
+                if let Some((time_to_full, graph)) = derived.remove(&drive.info.mount_point) {
+                    drive.time_to_full = time_to_full;
+                    drive.graph = graph;
+                }
+            }
+        }
+        Err(why) => eprintln!("usage history worker panicked: {why}"),
+    }
+}
+
+/// Reads the block group allocation of a btrfs drive, if it is one.
+fn read_btrfs_usage(info: &DriveInfo) -> Option<BtrfsUsage> {
+    if info.fs_type != "btrfs" {
+        return None;
+    }
+
+    let uuid = info.uuid.as_ref()?;
+    btrfs::read_usage(uuid)
+        .inspect_err(|why| eprintln!("failed to read btrfs allocation for {}: {why}", info.mount_point.display()))
+        .ok()
+}
+
+/// Reads the current user's quota on a local drive, on a blocking worker.
+async fn read_quota(info: &DriveInfo, timeout: Duration) -> Option<QuotaInfo> {
+    // quotactl needs a block device
+    if !info.device.starts_with("/dev/") {
+        return None;
+    }
+
+    space::get_quota_info_async(info.device.clone(), info.mount_point.clone(), timeout).await
+}
+
+/// Delay before reconnecting the UDisks2 mount watcher.
+const MOUNT_WATCH_RETRY: Duration = Duration::from_secs(30);
+
+/// Streams the drive list from UDisks2 whenever a filesystem is mounted or unmounted.
+///
+/// If the system bus or UDisks2 is unavailable or goes away, sends the
+/// drives from mountinfo instead and reconnects after a delay.
+fn mount_events() -> impl cosmic::iced::futures::Stream<Item = Message> {
+    stream::channel(4, |mut output| async move {
+        loop {
+            match udisks::MountWatcher::new().await {
+                Ok(mut watcher) => loop {
+                    // The bus can be up without the UDisks2 daemon, in which
+                    // case no signals will ever arrive
+                    match watcher.scan().await {
+                        Ok((mounted, unmounted)) => {
+                            if output.send(Message::MountsChanged(mounted, unmounted)).await.is_err() {
+                                return;
+                            }
+                        }
+                        Err(why) => {
+                            eprintln!("failed to enumerate drives via UDisks2: {why}");
+                            break;
+                        }
+                    }
+
+                    if let Err(why) = watcher.changed().await {
+                        eprintln!("lost UDisks2 mount events: {why}");
+                        break;
+                    }
+                },
+                Err(why) => eprintln!("failed to watch UDisks2 mounts: {why}"),
+            }
+
+            // Without UDisks2, re-read mountinfo on each retry instead
+            match mountinfo::enumerate_drives() {
+                Ok(mounted) => {
+                    if output.send(Message::MountsChanged(mounted, Vec::new())).await.is_err() {
+                        return;
+                    }
+                }
+                Err(why) => eprintln!("failed to enumerate drives from mountinfo: {why}"),
+            }
+
+            tokio::time::sleep(MOUNT_WATCH_RETRY).await;
+        }
+    })
+}
+
+/// Runs the session D-Bus service and forwards client requests to the applet.
+fn service_events(drives: SharedDrives) -> impl cosmic::iced::futures::Stream<Item = Message> {
+    stream::channel(4, |mut output| async move {
+        let (requests, mut received) = tokio::sync::mpsc::channel(4);
+
+        let connection = match service::start(drives, requests).await {
+            Ok(connection) => connection,
+            Err(why) => {
+                // Most likely another instance already owns the name
+                eprintln!("failed to start D-Bus service: {why}");
+                return;
+            }
+        };
+
+        if output.send(Message::ServiceReady(connection)).await.is_err() {
+            return;
+        }
+
+        while let Some(request) = received.recv().await {
+            if output.send(Message::ServiceRequest(request)).await.is_err() {
+                return;
+            }
+        }
+    })
+}
+
+/// Delay before reconnecting the notification action listener.
+const NOTIFICATION_WATCH_RETRY: Duration = Duration::from_secs(30);
+
+/// Streams button presses and closes of notifications from the session bus.
+///
+/// Reconnects after a delay if the session bus connection is lost.
+fn notification_events() -> impl cosmic::iced::futures::Stream<Item = Message> {
+    stream::channel(4, |mut output| async move {
+        loop {
+            match notifications::ActionWatcher::new().await {
+                Ok(mut watcher) => {
+                    if output.send(Message::NotifierReady(watcher.connection().clone())).await.is_err() {
+                        return;
+                    }
+
+                    loop {
+                        match watcher.next().await {
+                            Ok(event) => {
+                                if output.send(Message::NotificationEvent(event)).await.is_err() {
+                                    return;
+                                }
+                            }
+                            Err(why) => {
+                                eprintln!("lost notification actions: {why}");
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(why) => eprintln!("failed to listen for notification actions: {why}"),
+            }
+
+            tokio::time::sleep(NOTIFICATION_WATCH_RETRY).await;
+        }
+    })
+}
+
+/// Returns the symbolic icon name for a drive's card.
+fn drive_icon(info: &DriveInfo) -> &'static str {
+    if info.network {
+        "folder-remote-symbolic"
+    } else if info.removable {
+        "drive-removable-media-symbolic"
+    } else {
+        "drive-harddisk-symbolic"
+    }
+}
+
+/// Returns the label shown for a kind of reclaimable space.
+fn cleanup_label(kind: CleanupKind) -> String {
+    match kind {
+        CleanupKind::Trash => fl!("reclaim-trash"),
+        CleanupKind::Cache => fl!("reclaim-cache"),
+        CleanupKind::RustTargets => fl!("reclaim-rust-targets"),
+        CleanupKind::FlatpakRuntimes => fl!("reclaim-flatpak"),
+        CleanupKind::Journal => fl!("reclaim-journal"),
+    }
+}
+
+/// Enumerates drives via UDisks2, falling back to `/proc/self/mountinfo`
+/// when the system bus or the UDisks2 daemon is unavailable.
+async fn scan_drives() -> (Vec<DriveInfo>, Vec<UnmountedFs>) {
+    let why = match udisks::scan().await {
+        Ok(scan) => return scan,
+        Err(why) => why,
+    };
+
+    eprintln!("failed to enumerate drives via UDisks2, using mountinfo: {why}");
+    match mountinfo::enumerate_drives() {
+        Ok(mounted) => (mounted, Vec::new()),
+        Err(why) => {
+            eprintln!("failed to enumerate drives: {why}");
+            (Vec::new(), Vec::new())
+        }
+    }
 }
 
 /// Returns a text style using the theme's destructive color.
@@ -504,3 +2782,142 @@ fn danger_text_style(theme: &Theme) -> cosmic::iced_widget::text::Style {
         color: Some(theme.cosmic().destructive_color().into()),
     }
 }
+
+/// Returns a text style using the theme's warning color.
+fn warning_text_style(theme: &Theme) -> cosmic::iced_widget::text::Style {
+    cosmic::iced_widget::text::Style {
+        color: Some(theme.cosmic().warning_color().into()),
+    }
+}
+
+/// Returns a text style using the theme's success color.
+fn success_text_style(theme: &Theme) -> cosmic::iced_widget::text::Style {
+    cosmic::iced_widget::text::Style {
+        color: Some(theme.cosmic().success_color().into()),
+    }
+}
+
+/// Returns a text style for drives that are not responding.
+fn dim_text_style(theme: &Theme) -> cosmic::iced_widget::text::Style {
+    let mut color = theme.cosmic().background.on;
+    color.alpha = 0.5;
+    cosmic::iced_widget::text::Style {
+        color: Some(color.into()),
+    }
+}
+
+/// Returns a progress bar style using the theme's warning color.
+fn warning_bar_style(theme: &Theme) -> cosmic::iced_widget::progress_bar::Style {
+    let cosmic = theme.cosmic();
+    cosmic::iced_widget::progress_bar::Style {
+        background: cosmic::iced::Color::from(cosmic.background.divider).into(),
+        bar: cosmic::iced::Color::from(cosmic.warning_color()).into(),
+        border: cosmic::iced::Border {
+            radius: cosmic.corner_radii.radius_xs.into(),
+            ..Default::default()
+        },
+    }
+}
+
+/// Returns a greyed-out progress bar style for drives that are not responding.
+fn stale_bar_style(theme: &Theme) -> cosmic::iced_widget::progress_bar::Style {
+    let cosmic = theme.cosmic();
+    cosmic::iced_widget::progress_bar::Style {
+        background: cosmic::iced::Color::from(cosmic.background.divider).into(),
+        bar: cosmic::iced::Color::from(cosmic.palette.neutral_5).into(),
+        border: cosmic::iced::Border {
+            radius: cosmic.corner_radii.radius_xs.into(),
+            ..Default::default()
+        },
+    }
+}
+
+/// Returns the style of a usage graph column.
+fn graph_bar_style(theme: &Theme) -> cosmic::iced_widget::container::Style {
+    cosmic::iced_widget::container::Style {
+        background: Some(cosmic::iced::Color::from(theme.cosmic().accent_color()).into()),
+        ..Default::default()
+    }
+}
+
+/// Returns the style of a usage graph column that is over the warning threshold.
+fn graph_warning_style(theme: &Theme) -> cosmic::iced_widget::container::Style {
+    cosmic::iced_widget::container::Style {
+        background: Some(cosmic::iced::Color::from(theme.cosmic().warning_color()).into()),
+        ..Default::default()
+    }
+}
+
+/// Returns the style of a usage graph column that is over the critical threshold.
+fn graph_danger_style(theme: &Theme) -> cosmic::iced_widget::container::Style {
+    cosmic::iced_widget::container::Style {
+        background: Some(cosmic::iced::Color::from(theme.cosmic().destructive_color()).into()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn queued_alerts_are_delivered_after_quiet_hours() {
+        let mut states = HashMap::new();
+        let mut queue = QuietQueue::default();
+        let start = Instant::now();
+
+        // Quota is keyed by device and temperature by drive, not by mount point
+        let quota = (PathBuf::from("/dev/sda2"), AlertKind::Quota);
+        let temperature = (
+            PathBuf::from("/org/freedesktop/UDisks2/drives/Samsung_SSD"),
+            AlertKind::Temperature,
+        );
+
+        // Raised during quiet hours
+        for key in [&quota, &temperature] {
+            assert!(should_alert(&mut states, key.clone(), true, start, COOLDOWN));
+        }
+        queue.push(quota.clone(), "quota".to_string());
+        queue.push(temperature.clone(), "temperature".to_string());
+        queue.retain_active(&states);
+
+        // Still active on the first check after quiet hours end
+        let later = start + Duration::from_secs(60);
+        for key in [&quota, &temperature] {
+            assert!(!should_alert(&mut states, key.clone(), true, later, COOLDOWN));
+        }
+        queue.retain_active(&states);
+
+        assert_eq!(queue.take(), ["quota", "temperature"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn queued_alert_is_dropped_once_cleared() {
+        let mut states = HashMap::new();
+        let mut queue = QuietQueue::default();
+        let start = Instant::now();
+        let key = (PathBuf::from("/dev/sda2"), AlertKind::Quota);
+
+        should_alert(&mut states, key.clone(), true, start, COOLDOWN);
+        queue.push(key.clone(), "quota".to_string());
+
+        should_alert(&mut states, key, false, start + Duration::from_secs(60), COOLDOWN);
+        queue.retain_active(&states);
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn repeated_alert_replaces_queued_one() {
+        let mut queue = QuietQueue::default();
+        let key = (PathBuf::from("/"), AlertKind::PredictedFull);
+
+        queue.push(key.clone(), "in 5 hours".to_string());
+        queue.push(key, "in 2 hours".to_string());
+
+        assert_eq!(queue.take(), ["in 2 hours"]);
+    }
+}
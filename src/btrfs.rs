@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Btrfs block group allocation from `/sys/fs/btrfs/<uuid>/allocation`.
+//!
+//! statvfs on btrfs reports a single used/free figure, but space is handed
+//! out in data, metadata and system chunks. A filesystem runs out of space
+//! when metadata chunks are full and nothing is left unallocated, even if
+//! statvfs still shows plenty free.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Bytes per sector in sysfs block device `size` files.
+const SECTOR_SIZE: u64 = 512;
+
+/// Usage of one kind of block group (data, metadata or system).
+#[derive(Debug, Clone, Default)]
+pub struct BlockGroup {
+    /// Bytes allocated to this kind of chunk.
+    pub total: u64,
+    /// Bytes used within the allocated chunks.
+    pub used: u64,
+    /// Raw bytes allocated on disk, counting every copy.
+    pub disk_total: u64,
+    /// RAID profile (e.g. single, dup, raid1), if known.
+    pub profile: Option<String>,
+}
+
+/// Allocation breakdown for a btrfs filesystem.
+#[derive(Debug, Clone, Default)]
+pub struct BtrfsUsage {
+    /// File contents.
+    pub data: BlockGroup,
+    /// Trees describing files, directories and extents.
+    pub metadata: BlockGroup,
+    /// The chunk tree.
+    pub system: BlockGroup,
+    /// Bytes reserved out of metadata for critical operations.
+    pub global_reserve: u64,
+    /// Raw device bytes not allocated to any chunk.
+    pub unallocated: u64,
+}
+
+impl BtrfsUsage {
+    /// Returns how full metadata is, counting the global reserve as used
+    /// and unallocated space as room metadata could still grow into.
+    pub fn metadata_percent_used(&self) -> u8 {
+        let metadata = &self.metadata;
+
+        // dup and raid1 metadata take more than one byte of disk per byte stored
+        let copies = if metadata.total == 0 {
+            1.0
+        } else {
+            (metadata.disk_total as f64 / metadata.total as f64).max(1.0)
+        };
+
+        let used = (metadata.used + self.global_reserve) as f64;
+        let capacity = metadata.total as f64 + self.unallocated as f64 / copies;
+        if capacity == 0.0 {
+            return 0;
+        }
+
+        ((used / capacity) * 100.0).round().min(100.0) as u8
+    }
+}
+
+/// Reads the allocation of the btrfs filesystem with the given UUID.
+pub fn read_usage(uuid: &str) -> Result<BtrfsUsage> {
+    let root = Path::new("/sys/fs/btrfs").join(uuid);
+    let allocation = root.join("allocation");
+
+    let data = read_block_group(&allocation.join("data"))?;
+    let metadata = read_block_group(&allocation.join("metadata"))?;
+    let system = read_block_group(&allocation.join("system"))?;
+    let global_reserve = read_u64(&allocation.join("global_rsv_size")).unwrap_or(0);
+
+    let device_size = device_size(&root.join("devices"))?;
+    let allocated = data.disk_total + metadata.disk_total + system.disk_total;
+
+    Ok(BtrfsUsage {
+        data,
+        metadata,
+        system,
+        global_reserve,
+        unallocated: device_size.saturating_sub(allocated),
+    })
+}
+
+fn read_block_group(dir: &Path) -> Result<BlockGroup> {
+    // Each profile in use has its own subdirectory, e.g. allocation/metadata/dup
+    let profile = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .flatten()
+        .find(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned());
+
+    Ok(BlockGroup {
+        total: read_u64(&dir.join("total_bytes"))?,
+        used: read_u64(&dir.join("bytes_used"))?,
+        disk_total: read_u64(&dir.join("disk_total"))?,
+        profile,
+    })
+}
+
+/// Sums the sizes of the block devices backing the filesystem.
+fn device_size(devices: &Path) -> Result<u64> {
+    let mut total = 0;
+
+    for entry in fs::read_dir(devices).with_context(|| format!("failed to read {}", devices.display()))? {
+        let entry = entry.with_context(|| format!("failed to read {}", devices.display()))?;
+        total += read_u64(&entry.path().join("size"))? * SECTOR_SIZE;
+    }
+
+    Ok(total)
+}
+
+fn read_u64(path: &Path) -> Result<u64> {
+    fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid number in {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn with_metadata(total: u64, disk_total: u64, used: u64, global_reserve: u64, unallocated: u64) -> BtrfsUsage {
+        BtrfsUsage {
+            metadata: BlockGroup {
+                total,
+                used,
+                disk_total,
+                profile: None,
+            },
+            global_reserve,
+            unallocated,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_metadata_counts_unallocated_space_once() {
+        // 5 GiB used of 10 allocated + 40 unallocated
+        let usage = with_metadata(10 * GIB, 10 * GIB, 4 * GIB, GIB, 40 * GIB);
+        assert_eq!(usage.metadata_percent_used(), 10);
+    }
+
+    #[test]
+    fn dup_metadata_halves_unallocated_space() {
+        // Two copies on one device: 40 GiB unallocated holds 20 GiB of metadata
+        let usage = with_metadata(10 * GIB, 20 * GIB, 4 * GIB, 2 * GIB, 40 * GIB);
+        assert_eq!(usage.metadata_percent_used(), 20);
+    }
+
+    #[test]
+    fn raid1_metadata_halves_unallocated_space() {
+        // Two copies across devices: 80 GiB unallocated holds 40 GiB of metadata
+        let usage = with_metadata(10 * GIB, 20 * GIB, 4 * GIB, GIB, 80 * GIB);
+        assert_eq!(usage.metadata_percent_used(), 10);
+    }
+
+    #[test]
+    fn fully_allocated_metadata_is_judged_by_its_chunks() {
+        let usage = with_metadata(10 * GIB, 10 * GIB, 9 * GIB, GIB / 2, 0);
+        assert_eq!(usage.metadata_percent_used(), 95);
+
+        // The reserve can push usage past the allocated chunks
+        let usage = with_metadata(10 * GIB, 10 * GIB, 10 * GIB, GIB, 0);
+        assert_eq!(usage.metadata_percent_used(), 100);
+    }
+
+    #[test]
+    fn empty_filesystem_is_zero() {
+        assert_eq!(BtrfsUsage::default().metadata_percent_used(), 0);
+    }
+}
@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Finds and removes common space hogs: the Trash, `~/.cache`, stale Rust
+//! `target/` directories, unused Flatpak runtimes and journal files beyond a
+//! size cap.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use crate::analyzer;
+use crate::space;
+
+/// A Rust `target/` directory untouched for this long is considered stale.
+const STALE_TARGET_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// How deep to look for Cargo projects under each root.
+const TARGET_SEARCH_DEPTH: usize = 4;
+/// Archived journal files are removed until the journal fits in this many MiB.
+const JOURNAL_KEEP_MIB: u64 = 256;
+/// System-wide Flatpak installation.
+const FLATPAK_SYSTEM_DIR: &str = "/var/lib/flatpak";
+
+/// Kinds of reclaimable space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CleanupKind {
+    /// The user's Trash.
+    Trash,
+    /// The user's cache directory.
+    Cache,
+    /// Rust `target/` directories not built in a while.
+    RustTargets,
+    /// Flatpak runtimes no installed app needs.
+    FlatpakRuntimes,
+    /// Archived systemd journal files beyond [`JOURNAL_KEEP_MIB`].
+    Journal,
+}
+
+/// Space that can be reclaimed, found by [`find_reclaimable`].
+#[derive(Debug, Clone)]
+pub struct Reclaimable {
+    pub kind: CleanupKind,
+    /// Estimated bytes that cleaning frees.
+    pub size: u64,
+    /// Files or directories that cleaning removes, or paths on the affected
+    /// filesystems for items cleaned by an external command.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Finds reclaimable items on a blocking worker.
+pub async fn find_reclaimable_async(target_roots: Vec<PathBuf>) -> Result<Vec<Reclaimable>> {
+    tokio::task::spawn_blocking(move || find_reclaimable(&target_roots))
+        .await
+        .context("cleanup scan worker panicked")
+}
+
+/// Cleans an item on a blocking worker and returns the bytes freed.
+pub async fn clean_async(item: Reclaimable) -> Result<u64> {
+    tokio::task::spawn_blocking(move || clean(&item))
+        .await
+        .context("cleanup worker panicked")?
+}
+
+/// Finds and sizes reclaimable items, skipping any that are empty.
+///
+/// `target_roots` are searched for stale Rust `target/` directories.
+pub fn find_reclaimable(target_roots: &[PathBuf]) -> Vec<Reclaimable> {
+    let mut items = Vec::new();
+
+    if let Some(trash) = dirs::data_local_dir().map(|d| d.join("Trash")) {
+        let paths = vec![trash.join("files"), trash.join("info"), trash.join("expunged")];
+        let size = paths.iter().map(|p| analyzer::disk_size(p)).sum();
+        items.push(Reclaimable {
+            kind: CleanupKind::Trash,
+            size,
+            paths,
+        });
+    }
+
+    if let Some(cache) = dirs::cache_dir() {
+        items.push(Reclaimable {
+            kind: CleanupKind::Cache,
+            size: analyzer::disk_size(&cache),
+            paths: vec![cache],
+        });
+    }
+
+    let targets = stale_targets(target_roots);
+    items.push(Reclaimable {
+        kind: CleanupKind::RustTargets,
+        size: targets.iter().map(|p| analyzer::disk_size(p)).sum(),
+        paths: targets,
+    });
+
+    match unused_flatpak_size() {
+        Ok(size) => items.push(Reclaimable {
+            kind: CleanupKind::FlatpakRuntimes,
+            size,
+            paths: flatpak_installations(),
+        }),
+        Err(why) => eprintln!("failed to size unused Flatpak runtimes: {why}"),
+    }
+
+    match journal_size() {
+        Ok(size) => items.push(Reclaimable {
+            kind: CleanupKind::Journal,
+            size: size.saturating_sub(JOURNAL_KEEP_MIB * 1024 * 1024),
+            paths: vec![PathBuf::from("/var/log/journal")],
+        }),
+        Err(why) => eprintln!("failed to size the journal: {why}"),
+    }
+
+    items.retain(|item| item.size > 0);
+    items
+}
+
+/// Cleans an item and returns the bytes freed.
+///
+/// Freed space is measured with statvfs before and after on every
+/// filesystem the item's paths are on, so it reflects what the filesystems
+/// actually got back.
+pub fn clean(item: &Reclaimable) -> Result<u64> {
+    if item.paths.is_empty() {
+        anyhow::bail!("nothing to clean");
+    }
+    let before = available_space(&item.paths)?;
+
+    match item.kind {
+        CleanupKind::Trash | CleanupKind::Cache => {
+            for dir in &item.paths {
+                remove_contents(dir);
+            }
+        }
+        CleanupKind::RustTargets => {
+            for target in &item.paths {
+                if let Err(why) = fs::remove_dir_all(target) {
+                    eprintln!("failed to remove {}: {why}", target.display());
+                }
+            }
+        }
+        CleanupKind::FlatpakRuntimes => {
+            run(Command::new("flatpak").args(["uninstall", "--unused", "--noninteractive", "-y"]))?;
+        }
+        CleanupKind::Journal => {
+            // The system journal belongs to root, so ask through polkit
+            run(Command::new("pkexec").args(["journalctl", &format!("--vacuum-size={JOURNAL_KEEP_MIB}M")]))?;
+        }
+    }
+
+    let after = available_space(&item.paths)?;
+    Ok(after
+        .iter()
+        .map(|(dev, available)| available.saturating_sub(before.get(dev).copied().unwrap_or(*available)))
+        .sum())
+}
+
+/// Returns the available bytes on each distinct filesystem holding `paths`,
+/// keyed by device number.
+fn available_space(paths: &[PathBuf]) -> Result<HashMap<u64, u64>> {
+    let mut filesystems = HashMap::new();
+
+    for path in paths {
+        let probe = existing_ancestor(path);
+        let dev = fs::metadata(&probe)
+            .with_context(|| format!("failed to stat {}", probe.display()))?
+            .dev();
+        if let Entry::Vacant(entry) = filesystems.entry(dev) {
+            entry.insert(space::get_space_info(&probe)?.available);
+        }
+    }
+
+    Ok(filesystems)
+}
+
+/// Removes everything inside a directory, keeping the directory itself.
+///
+/// Entries that can't be removed (e.g. in use or owned by another user) are
+/// logged and skipped.
+fn remove_contents(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let result = match entry.file_type() {
+            Ok(t) if t.is_dir() => fs::remove_dir_all(&path),
+            _ => fs::remove_file(&path),
+        };
+        if let Err(why) = result {
+            eprintln!("failed to remove {}: {why}", path.display());
+        }
+    }
+}
+
+/// Finds `target/` directories next to a `Cargo.toml` that haven't been
+/// built in [`STALE_TARGET_AGE`].
+fn stale_targets(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut seen = HashSet::new();
+
+    for root in roots {
+        find_targets(root, TARGET_SEARCH_DEPTH, &mut seen, &mut found);
+    }
+
+    found
+}
+
+fn find_targets(dir: &Path, depth: usize, seen: &mut HashSet<PathBuf>, found: &mut Vec<PathBuf>) {
+    if !seen.insert(dir.to_path_buf()) {
+        return;
+    }
+
+    let target = dir.join("target");
+    if dir.join("Cargo.toml").is_file() && target.is_dir() && is_stale(&target) {
+        found.push(target);
+    }
+
+    if depth == 0 {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let skip = name.to_string_lossy().starts_with('.') || name == "target" || name == "node_modules";
+        if !skip && entry.file_type().is_ok_and(|t| t.is_dir()) {
+            find_targets(&entry.path(), depth - 1, seen, found);
+        }
+    }
+}
+
+/// Returns true if neither `target/` nor its profile directories changed recently.
+fn is_stale(target: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    let newest = fs::read_dir(target)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| modified(&entry.path()))
+        .chain(modified(target))
+        .max();
+
+    newest
+        .and_then(|time| SystemTime::now().duration_since(time).ok())
+        .is_some_and(|age| age >= STALE_TARGET_AGE)
+}
+
+/// Returns the Flatpak installations that exist: system-wide and the
+/// user's own in `~/.local/share/flatpak`.
+fn flatpak_installations() -> Vec<PathBuf> {
+    std::iter::once(PathBuf::from(FLATPAK_SYSTEM_DIR))
+        .chain(dirs::data_dir().map(|dir| dir.join("flatpak")))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Estimates the size of runtimes no installed app uses, across the system
+/// and user installations.
+fn unused_flatpak_size() -> Result<u64> {
+    let apps = command_output(Command::new("flatpak").args(["list", "--app", "--columns=application,runtime"]))?;
+    let runtimes = command_output(Command::new("flatpak").args(["list", "--runtime", "--columns=ref,size"]))?;
+    Ok(unused_runtime_size(&apps, &runtimes))
+}
+
+/// Sums the sizes of runtimes in `flatpak list --runtime` output that no app
+/// in `flatpak list --app` output uses.
+///
+/// A runtime counts as used if an app runs on it, or if it is an extension
+/// of a used runtime or an app (e.g. `org.freedesktop.Platform.GL.default`).
+/// `flatpak uninstall --unused` makes the final decision when cleaning.
+fn unused_runtime_size(apps: &str, runtimes: &str) -> u64 {
+    let mut used_refs = HashSet::new();
+    let mut used_names = HashSet::new();
+    for line in apps.lines() {
+        let mut fields = line.split('\t');
+        if let Some(app) = fields.next() {
+            used_names.insert(app.to_string());
+        }
+        if let Some(runtime) = fields.next() {
+            used_refs.insert(runtime.to_string());
+            used_names.insert(runtime.split('/').next().unwrap_or(runtime).to_string());
+        }
+    }
+
+    runtimes
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(flatpak_ref, _)| {
+            let name = flatpak_ref.split('/').next().unwrap_or(flatpak_ref);
+            let is_extension = used_names.iter().any(|used| name.starts_with(&format!("{used}.")));
+            !used_refs.contains(*flatpak_ref) && !is_extension
+        })
+        .filter_map(|(_, size)| parse_size(size, 1000))
+        .sum()
+}
+
+/// Returns the size of all journal files, archived and active, from
+/// `journalctl --disk-usage`.
+fn journal_size() -> Result<u64> {
+    // "Archived and active journals take up 1.2G in the file system."
+    let output = command_output(Command::new("journalctl").arg("--disk-usage"))?;
+    output
+        .split_whitespace()
+        .skip_while(|word| *word != "up")
+        .nth(1)
+        .and_then(|size| parse_size(size, 1024))
+        .context("unexpected journalctl output")
+}
+
+/// Parses sizes like "1.2 GB", "318.6 MB", "750 bytes" or "1.2G".
+///
+/// `base` is 1000 for SI units as printed by Flatpak and 1024 for systemd's.
+fn parse_size(text: &str, base: u64) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let exponent = match unit.trim().chars().next().map(|c| c.to_ascii_uppercase()) {
+        None | Some('B') => 0,
+        Some('K') => 1,
+        Some('M') => 2,
+        Some('G') => 3,
+        Some('T') => 4,
+        Some('P') => 5,
+        _ => return None,
+    };
+
+    Some((number * (base as f64).powi(exponent)) as u64)
+}
+
+/// Runs a command and returns its stdout, failing on a non-zero exit.
+fn command_output(command: &mut Command) -> Result<String> {
+    let output = command.output().with_context(|| format!("failed to run {command:?}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{command:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    command_output(command).map(|_| ())
+}
+
+/// Returns the closest existing ancestor of `path`, for statvfs.
+fn existing_ancestor(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("/"))
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("750 bytes", 1000), Some(750));
+        assert_eq!(parse_size("1.2 GB", 1000), Some(1_200_000_000));
+        assert_eq!(parse_size("318.6 MB", 1000), Some(318_600_000));
+        // GLib separates the unit with a no-break space
+        assert_eq!(parse_size("12\u{a0}kB", 1000), Some(12_000));
+        assert_eq!(parse_size("1.5G", 1024), Some(1_610_612_736));
+        assert_eq!(parse_size(" 64M ", 1024), Some(67_108_864));
+        assert_eq!(parse_size("512", 1024), Some(512));
+    }
+
+    #[test]
+    fn rejects_unknown_sizes() {
+        assert_eq!(parse_size("", 1000), None);
+        assert_eq!(parse_size("unknown", 1000), None);
+        assert_eq!(parse_size("3 XB", 1000), None);
+    }
+
+    #[test]
+    fn sums_unused_runtimes() {
+        let apps = "org.mozilla.firefox\torg.freedesktop.Platform/x86_64/23.08\n\
+                    org.gnome.Calculator\torg.gnome.Platform/x86_64/46\n";
+        let runtimes = "org.freedesktop.Platform/x86_64/23.08\t500.0 MB\n\
+                        org.freedesktop.Platform.GL.default/x86_64/23.08\t100.0 MB\n\
+                        org.mozilla.firefox.Locale/x86_64/stable\t10.0 MB\n\
+                        org.gnome.Platform/x86_64/46\t400.0 MB\n\
+                        org.gnome.Platform/x86_64/45\t300.0 MB\n\
+                        org.kde.Platform/x86_64/6.7\t200.0 MB\n";
+
+        // Only the old GNOME runtime and the KDE runtime are unused
+        assert_eq!(unused_runtime_size(apps, runtimes), 500_000_000);
+    }
+
+    #[test]
+    fn no_apps_leaves_every_runtime_unused() {
+        let runtimes = "org.freedesktop.Platform/x86_64/23.08\t500.0 MB\n";
+        assert_eq!(unused_runtime_size("", runtimes), 500_000_000);
+        assert_eq!(unused_runtime_size("", ""), 0);
+    }
+}
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Headless commands for scripts, cron jobs and CI runners.
+//!
+//! These read the applet's stored config, so thresholds and the list of
+//! monitored drives match what the panel applet uses.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use cosmic::Application;
+use serde::Serialize;
+
+use crate::applet::CargoWatch;
+use crate::config::{AlertLevel, Config};
+use crate::space;
+use crate::udisks::{self, DriveInfo};
+use crate::{mountinfo, zfs};
+
+const USAGE: &str = "\
+Usage: cosmic-ext-applet-cargowatch [COMMAND [--json]]
+
+Runs the panel applet when no command is given.
+
+Commands:
+  status       Show disk usage of monitored drives
+  check        Exit with status 1 if any monitored drive is over its thresholds,
+               or 2 if a drive did not respond
+  list-drives  List every mounted filesystem and whether it is monitored
+
+Options:
+  --json       Print JSON instead of a table
+  -h, --help   Print this help";
+
+/// Runs a command from the command line and returns its exit status: 0 on
+/// success, 1 if `check` found a drive over its thresholds, 2 on errors or
+/// if `check` found a drive that did not respond.
+///
+/// Returns `None` if the first argument isn't a command and the applet
+/// should start. Anything else the panel or a launcher passes, such as the
+/// desktop entry's `%F`, is left to the applet.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = match args.first().map(String::as_str) {
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            return Some(0);
+        }
+        Some(command @ ("status" | "check" | "list-drives")) => command,
+        _ => return None,
+    };
+
+    let mut json = false;
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Some(0);
+            }
+            _ => {
+                eprintln!("unexpected argument: {arg}\n\n{USAGE}");
+                return Some(2);
+            }
+        }
+    }
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(why) => {
+            eprintln!("failed to start async runtime: {why}");
+            return Some(2);
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let (config, _) = Config::load(CargoWatch::APP_ID);
+        match command {
+            "status" => status(&config, json).await.map(|_| 0),
+            "check" => check(&config, json).await,
+            _ => list_drives(&config, json).await.map(|_| 0),
+        }
+    });
+
+    Some(result.unwrap_or_else(|why| {
+        eprintln!("{why:#}");
+        2
+    }))
+}
+
+/// A monitored drive and how its usage compares to its thresholds.
+#[derive(Serialize)]
+struct DriveStatusJson {
+    id: String,
+    mount_point: String,
+    label: String,
+    device: String,
+    fs_type: String,
+    total: u64,
+    used: u64,
+    available: u64,
+    percent: u8,
+    /// "normal", "warning" or "critical"; ZFS datasets use their pool's level
+    /// when `zpool` could list it.
+    /// `null` if the drive did not respond.
+    level: Option<AlertLevel>,
+    alerts_enabled: bool,
+    /// True if statvfs hung or failed; the usage fields are then zero.
+    unresponsive: bool,
+}
+
+/// A mounted filesystem, as listed by `list-drives`.
+#[derive(Serialize)]
+struct DriveJson {
+    id: String,
+    mount_point: String,
+    label: String,
+    device: String,
+    fs_type: String,
+    removable: bool,
+    network: bool,
+    monitored: bool,
+}
+
+async fn status(config: &Config, json: bool) -> Result<()> {
+    let drives = drive_status(config).await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&drives)?);
+        return Ok(());
+    }
+
+    println!("{:<24} {:>10} {:>10} {:>5}  LEVEL", "MOUNT", "USED", "SIZE", "USE%");
+    for drive in &drives {
+        let Some(level) = drive.level else {
+            println!("{:<24} {:>10} {:>10} {:>5}  unresponsive", drive.mount_point, "-", "-", "-");
+            continue;
+        };
+        println!(
+            "{:<24} {:>10} {:>10} {:>4}%  {}",
+            drive.mount_point,
+            space::format_bytes(drive.used),
+            space::format_bytes(drive.total),
+            drive.percent,
+            level.as_str()
+        );
+    }
+
+    Ok(())
+}
+
+async fn check(config: &Config, json: bool) -> Result<i32> {
+    // A drive that didn't respond can't be vouched for, so it fails the check too
+    let failing: Vec<_> = drive_status(config)
+        .await
+        .into_iter()
+        .filter(|drive| drive.alerts_enabled && drive.level != Some(AlertLevel::Normal))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&failing)?);
+    } else {
+        for drive in &failing {
+            match drive.level {
+                Some(level) => println!("{}: {}% used ({})", drive.mount_point, drive.percent, level.as_str()),
+                None => println!("{}: unresponsive", drive.mount_point),
+            }
+        }
+    }
+
+    Ok(if failing.iter().any(|drive| drive.unresponsive) {
+        2
+    } else if failing.is_empty() {
+        0
+    } else {
+        1
+    })
+}
+
+async fn list_drives(config: &Config, json: bool) -> Result<()> {
+    let drives: Vec<_> = mounted_drives(config)
+        .await
+        .into_iter()
+        .map(|info| DriveJson {
+            monitored: config.monitors(&info),
+            id: info.id.clone(),
+            mount_point: info.mount_point.display().to_string(),
+            label: info.display_name(),
+            device: info.device.clone(),
+            fs_type: info.fs_type.clone(),
+            removable: info.removable,
+            network: info.network,
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&drives)?);
+        return Ok(());
+    }
+
+    for drive in &drives {
+        let monitored = if drive.monitored { "monitored" } else { "-" };
+        println!(
+            "{:<24} {:<20} {:<8} {:<10} {}",
+            drive.mount_point, drive.device, drive.fs_type, monitored, drive.id
+        );
+    }
+
+    Ok(())
+}
+
+/// Enumerates mounted drives the same way the applet does: UDisks2 with a
+/// mountinfo fallback, plus ZFS datasets and network mounts if enabled.
+async fn mounted_drives(config: &Config) -> Vec<DriveInfo> {
+    let mut drives = match udisks::enumerate_drives().await {
+        Ok(drives) => drives,
+        Err(why) => {
+            eprintln!("failed to enumerate drives via UDisks2, using mountinfo: {why}");
+            mountinfo::enumerate_drives().unwrap_or_else(|why| {
+                eprintln!("failed to enumerate drives from mountinfo: {why}");
+                Vec::new()
+            })
+        }
+    };
+
+    if config.monitor_network_mounts {
+        match mountinfo::network_mounts() {
+            Ok(network) => drives.extend(network),
+            Err(why) => eprintln!("failed to enumerate network mounts: {why}"),
+        }
+    }
+
+    match mountinfo::zfs_datasets() {
+        Ok(datasets) => drives.extend(datasets),
+        Err(why) => eprintln!("failed to enumerate ZFS datasets: {why}"),
+    }
+
+    drives
+}
+
+/// Queries space for every monitored drive and evaluates its thresholds.
+///
+/// Drives that fail or don't answer within the configured statvfs timeout
+/// are marked unresponsive.
+async fn drive_status(config: &Config) -> Vec<DriveStatusJson> {
+    let timeout = Duration::from_secs(config.statvfs_timeout);
+    let drives: Vec<_> = mounted_drives(config)
+        .await
+        .into_iter()
+        .filter(|info| config.monitors(info))
+        .collect();
+
+    // Without a pool list, datasets fall back to their own thresholds, like in the applet
+    let pools = if drives.iter().any(DriveInfo::is_zfs) {
+        match zfs::list_pools_async(timeout).await {
+            Ok(pools) => pools.unwrap_or_default(),
+            Err(why) => {
+                eprintln!("failed to list ZFS pools: {why:#}");
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let mut status = Vec::new();
+    for info in drives {
+        let space = match space::get_space_info_async(info.mount_point.clone(), timeout).await {
+            Ok(Some(space)) => Some(space),
+            Ok(None) => {
+                eprintln!("{} did not respond", info.mount_point.display());
+                None
+            }
+            Err(why) => {
+                eprintln!("{why:#}");
+                None
+            }
+        };
+
+        // ZFS datasets are judged by their pool's capacity, like in the applet
+        let pool = pools
+            .iter()
+            .find(|pool| info.is_zfs() && pool.name == zfs::pool_name(&info.device));
+        let (id, judged) = match pool {
+            Some(pool) => (pool.id(), pool.space()),
+            None => (info.id.clone(), space.clone().unwrap_or_default()),
+        };
+        let alert_config = config.get_drive_alert(&id);
+        let level = space.is_some().then(|| alert_config.level(&judged));
+        let space = space.unwrap_or_default();
+
+        status.push(DriveStatusJson {
+            id: info.id.clone(),
+            mount_point: info.mount_point.display().to_string(),
+            label: info.display_name(),
+            device: info.device.clone(),
+            fs_type: info.fs_type.clone(),
+            total: space.total,
+            used: space.used,
+            available: space.available,
+            percent: space.percent_used(),
+            level,
+            alerts_enabled: alert_config.enabled,
+            unresponsive: level.is_none(),
+        });
+    }
+
+    status
+}
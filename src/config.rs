@@ -1,41 +1,318 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use std::collections::HashMap;
-use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, ConfigGet, CosmicConfigEntry};
+
+use crate::hooks::HookEvent;
+use crate::space::SpaceInfo;
+use crate::udisks::DriveInfo;
+
+/// Bytes per GiB, for the absolute free space rule.
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// How far a drive's usage is past its thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertLevel {
+    /// Below the warning threshold.
+    Normal,
+    /// At or above the warning threshold.
+    Warning,
+    /// At or above the critical threshold.
+    Critical,
+}
+
+impl AlertLevel {
+    /// Returns the level's name, as used in machine-readable output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
 
 /// Per-drive alert configuration.
 #[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct DriveAlertConfig {
     /// Whether alerts are enabled for this drive.
     pub enabled: bool,
-    /// Usage percentage at which to trigger alerts.
-    pub threshold: u8,
+    /// Usage percentage at which to show a warning.
+    pub warning_threshold: u8,
+    /// Usage percentage at which to show a critical alert.
+    pub critical_threshold: u8,
+    /// Whether the percentage thresholds are evaluated.
+    #[serde(default = "default_true")]
+    pub percent_enabled: bool,
+    /// Treat the drive as critical when less than this many GiB are free.
+    #[serde(default)]
+    pub min_free_gib: Option<u32>,
+    /// Alert when the drive is predicted to fill up within this many hours.
+    #[serde(default)]
+    pub predict_full_hours: Option<u32>,
+    /// Inode usage percentage at which to warn. Inodes turn critical at
+    /// `critical_threshold`, or at this threshold if it is higher.
+    #[serde(default = "default_inode_threshold")]
+    pub inode_threshold: u8,
+    /// User quota percentage at which to trigger alerts.
+    #[serde(default = "default_quota_threshold")]
+    pub quota_threshold: u8,
+    /// Btrfs metadata percentage at which to warn. Metadata turns critical at
+    /// `critical_threshold`, or at this threshold if it is higher.
+    #[serde(default = "default_metadata_threshold")]
+    pub metadata_threshold: u8,
+    /// Commands run on usage threshold events.
+    #[serde(default)]
+    pub hooks: AlertHooks,
+}
+
+/// Shell commands run on a drive's usage threshold events. See [`crate::hooks`]
+/// for the environment they run with.
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AlertHooks {
+    /// Run when usage crosses the warning or critical threshold.
+    #[serde(default)]
+    pub crossed: Option<String>,
+    /// Run when usage is still over a threshold after the alert cooldown.
+    #[serde(default)]
+    pub still_over: Option<String>,
+    /// Run when usage drops back below the warning threshold.
+    #[serde(default)]
+    pub recovered: Option<String>,
+}
+
+impl AlertHooks {
+    /// Returns the command for an event, if one is set.
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        match event {
+            HookEvent::Crossed => self.crossed.as_deref(),
+            HookEvent::StillOver => self.still_over.as_deref(),
+            HookEvent::Recovered => self.recovered.as_deref(),
+        }
+        .filter(|command| !command.trim().is_empty())
+    }
+}
+
+fn default_inode_threshold() -> u8 {
+    90
+}
+
+fn default_quota_threshold() -> u8 {
+    90
+}
+
+fn default_metadata_threshold() -> u8 {
+    75
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for DriveAlertConfig {
     fn default() -> Self {
         Self {
             enabled: true,
-            threshold: 90,
+            warning_threshold: 80,
+            critical_threshold: 90,
+            percent_enabled: true,
+            min_free_gib: None,
+            predict_full_hours: None,
+            inode_threshold: default_inode_threshold(),
+            quota_threshold: default_quota_threshold(),
+            metadata_threshold: default_metadata_threshold(),
+            hooks: AlertHooks::default(),
         }
     }
 }
 
+impl DriveAlertConfig {
+    /// Returns the alert level for a drive, combining the percentage and
+    /// absolute free space rules.
+    pub fn level(&self, space: &SpaceInfo) -> AlertLevel {
+        if self.below_min_free(space.available) {
+            AlertLevel::Critical
+        } else {
+            self.percent_level(space.percent_used())
+        }
+    }
+
+    /// Returns true if `available` bytes is under the absolute free space minimum.
+    pub fn below_min_free(&self, available: u64) -> bool {
+        self.min_free_gib
+            .is_some_and(|gib| available < u64::from(gib) * GIB)
+    }
+
+    /// Returns the level an alert already raised is held at: usage must
+    /// drop `margin` percentage points below a threshold, or free space
+    /// rise `margin` GiB above the minimum, before it clears.
+    pub fn held_level(&self, space: &SpaceInfo, margin: u8) -> AlertLevel {
+        let min_free = self
+            .min_free_gib
+            .map(|gib| u64::from(gib.saturating_add(u32::from(margin))) * GIB);
+        if min_free.is_some_and(|min_free| space.available < min_free) {
+            AlertLevel::Critical
+        } else {
+            self.percent_level(space.percent_used().saturating_add(margin))
+        }
+    }
+
+    /// Returns the alert level for quota usage: a warning from the quota
+    /// threshold, critical once the quota is used up.
+    pub fn quota_level(&self, percent: u8) -> AlertLevel {
+        if percent >= 100 {
+            AlertLevel::Critical
+        } else if percent >= self.quota_threshold {
+            AlertLevel::Warning
+        } else {
+            AlertLevel::Normal
+        }
+    }
+
+    /// Returns the alert level for inode usage.
+    pub fn inode_level(&self, percent: u8) -> AlertLevel {
+        if percent >= self.critical_threshold.max(self.inode_threshold) {
+            AlertLevel::Critical
+        } else if percent >= self.inode_threshold {
+            AlertLevel::Warning
+        } else {
+            AlertLevel::Normal
+        }
+    }
+
+    /// Returns the alert level for btrfs metadata usage.
+    pub fn metadata_level(&self, percent: u8) -> AlertLevel {
+        if percent >= self.critical_threshold.max(self.metadata_threshold) {
+            AlertLevel::Critical
+        } else if percent >= self.metadata_threshold {
+            AlertLevel::Warning
+        } else {
+            AlertLevel::Normal
+        }
+    }
+
+    /// Returns the alert level for the given usage percentage alone.
+    pub fn percent_level(&self, percent: u8) -> AlertLevel {
+        if !self.percent_enabled {
+            AlertLevel::Normal
+        } else if percent >= self.critical_threshold {
+            AlertLevel::Critical
+        } else if percent >= self.warning_threshold {
+            AlertLevel::Warning
+        } else {
+            AlertLevel::Normal
+        }
+    }
+}
+
+/// A daily window, in local time, when non-critical alerts are held back.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct QuietHours {
+    /// Start of the window as "HH:MM", e.g. "22:00".
+    pub start: String,
+    /// End of the window as "HH:MM", e.g. "07:00". May be earlier than
+    /// `start` for a window that spans midnight.
+    pub end: String,
+}
+
+impl QuietHours {
+    /// Returns true if `minute` (minutes after local midnight) falls inside
+    /// the window. A window with an invalid time is never active.
+    pub fn contains(&self, minute: u16) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        }
+    }
+}
+
+/// Parses "HH:MM" into minutes after midnight.
+fn parse_time(time: &str) -> Option<u16> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// App id of the COSMIC notifications daemon's config.
+pub const NOTIFICATIONS_CONFIG_ID: &str = "com.system76.CosmicNotifications";
+
+/// The part of the COSMIC notifications daemon's config the applet follows.
+#[derive(Debug, Clone, Default, CosmicConfigEntry, Eq, PartialEq)]
+#[version = 1]
+pub struct NotificationsConfig {
+    /// Whether Do Not Disturb mode is on.
+    pub do_not_disturb: bool,
+}
+
+impl NotificationsConfig {
+    /// Reads the daemon's current settings, falling back to the defaults
+    /// for anything it hasn't written.
+    pub fn load() -> Self {
+        cosmic_config::Config::new(NOTIFICATIONS_CONFIG_ID, Self::VERSION)
+            .map(|handler| Self::get_entry(&handler).unwrap_or_else(|(_, config)| config))
+            .unwrap_or_default()
+    }
+}
+
 /// Applet configuration stored via cosmic-config.
 #[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
-#[version = 1]
+#[version = 2]
 pub struct Config {
     /// Seconds between disk space checks.
     pub poll_interval: u64,
-    /// Default usage percentage at which to trigger alerts (for drives without custom settings).
-    pub default_alert_threshold: u8,
-    /// Mount points to monitor. Empty means auto-detect all persistent drives.
+    /// Seconds to wait for statvfs before marking a drive unresponsive.
+    pub statvfs_timeout: u64,
+    /// Default warning percentage (for drives without custom settings).
+    pub default_warning_threshold: u8,
+    /// Default critical percentage (for drives without custom settings).
+    pub default_critical_threshold: u8,
+    /// Drive ids to monitor. Empty means auto-detect all persistent drives.
     pub monitored_drives: Vec<String>,
     /// Seconds before re-alerting for the same drive.
     pub alert_cooldown: u64,
-    /// Mount points to display on the panel.
+    /// Percentage points usage must drop below a threshold, and GiB free
+    /// space must rise above the minimum, before an alert clears, so a drive
+    /// hovering at the threshold doesn't flap.
+    pub hysteresis_margin: u8,
+    /// Whether to notify when a drive drops back below its warning threshold.
+    pub notify_recovery: bool,
+    /// Seconds a hook command may run before it is killed.
+    pub hook_timeout: u64,
+    /// Window when non-critical alerts are queued and delivered as one
+    /// summary once it ends. `None` disables quiet hours.
+    pub quiet_hours: Option<QuietHours>,
+    /// Drive temperature in degrees Celsius above which to alert.
+    pub temperature_limit: u8,
+    /// Whether to monitor network and FUSE mounts (NFS, CIFS, 9p, sshfs,
+    /// rclone, s3fs and GVfs).
+    ///
+    /// Off by default because statvfs on a network mount can be slow.
+    pub monitor_network_mounts: bool,
+    /// File to write drive metrics to on every refresh, for node_exporter's
+    /// textfile collector (e.g. `/var/lib/node_exporter/cargowatch.prom`).
+    /// `None` disables the exporter.
+    pub metrics_path: Option<String>,
+    /// Directories searched for stale Rust `target/` directories to reclaim.
+    ///
+    /// A leading `~` stands for the home directory.
+    pub cleanup_roots: Vec<String>,
+    /// Drive ids to display on the panel.
     pub panel_drives: Vec<String>,
-    /// Per-drive alert settings. Key is mount point path.
+    /// Per-drive alert settings, keyed by drive id.
+    ///
+    /// Drive ids come from `DriveInfo::id`. Entries written before drives had
+    /// stable ids are keyed by mount point until [`Config::migrate_drive_key`]
+    /// moves them.
     pub drive_alerts: HashMap<String, DriveAlertConfig>,
 }
 
@@ -43,9 +320,19 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             poll_interval: 30,
-            default_alert_threshold: 90,
+            statvfs_timeout: 5,
+            default_warning_threshold: 80,
+            default_critical_threshold: 90,
             monitored_drives: Vec::new(),
             alert_cooldown: 3600,
+            hysteresis_margin: 2,
+            notify_recovery: false,
+            hook_timeout: 300,
+            quiet_hours: None,
+            temperature_limit: 60,
+            monitor_network_mounts: false,
+            metrics_path: None,
+            cleanup_roots: vec!["~/Projects".to_string(), "~/src".to_string(), "~/code".to_string()],
             panel_drives: vec!["/".to_string(), "/home".to_string()],
             drive_alerts: HashMap::new(),
         }
@@ -53,14 +340,307 @@ impl Default for Config {
 }
 
 impl Config {
+    /// Loads the config for `app_id`, migrating from an older version if
+    /// the current one has never been written.
+    pub fn load(app_id: &str) -> (Self, Option<cosmic_config::Config>) {
+        let handler = match cosmic_config::Config::new(app_id, Self::VERSION) {
+            Ok(handler) => handler,
+            Err(why) => {
+                eprintln!("failed to load config: {why}");
+                return (Self::default(), None);
+            }
+        };
+
+        if !Self::has_saved_entry(app_id) {
+            if let Some(config) = ConfigV1::read(app_id).map(ConfigV1::migrate) {
+                if let Err(why) = config.write_entry(&handler) {
+                    eprintln!("failed to save migrated config: {why}");
+                }
+                return (config, Some(handler));
+            }
+        }
+
+        let config = Self::get_entry(&handler).unwrap_or_else(|(errors, config)| {
+            for why in errors {
+                eprintln!("failed to read config entry: {why}");
+            }
+            config
+        });
+
+        (config, Some(handler))
+    }
+
+    /// Returns true if any setting of the current version has been written,
+    /// i.e. its directory under `~/.config/cosmic/<app_id>` isn't empty.
+    fn has_saved_entry(app_id: &str) -> bool {
+        dirs::config_dir()
+            .map(|dir| dir.join("cosmic").join(app_id).join(format!("v{}", Self::VERSION)))
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .is_some_and(|mut entries| entries.next().is_some())
+    }
+
+    /// Re-keys settings stored under a drive's mount point to its stable id.
+    ///
+    /// Returns true if anything changed and the config should be saved.
+    pub fn migrate_drive_key(&mut self, mount_point: &str, id: &str) -> bool {
+        if mount_point == id {
+            return false;
+        }
+
+        let mut changed = false;
+
+        if let Some(alert) = self.drive_alerts.remove(mount_point) {
+            self.drive_alerts.entry(id.to_string()).or_insert(alert);
+            changed = true;
+        }
+
+        for list in [&mut self.panel_drives, &mut self.monitored_drives] {
+            if let Some(pos) = list.iter().position(|m| m == mount_point) {
+                if list.iter().any(|m| m == id) {
+                    list.remove(pos);
+                } else {
+                    list[pos] = id.to_string();
+                }
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Returns true if a drive should be monitored: one of the configured
+    /// drives, or any non-removable drive if none are configured. Network
+    /// mounts are only present when opted in, so they are always monitored.
+    pub fn monitors(&self, info: &DriveInfo) -> bool {
+        if self.monitored_drives.is_empty() {
+            !info.removable
+        } else {
+            info.network
+                || self
+                    .monitored_drives
+                    .iter()
+                    .any(|m| m == &info.id || info.mount_point == Path::new(m))
+        }
+    }
+
+    /// Returns the cleanup roots as paths, expanding a leading `~`.
+    pub fn cleanup_root_paths(&self) -> Vec<PathBuf> {
+        self.cleanup_roots
+            .iter()
+            .filter_map(|root| match root.strip_prefix('~') {
+                Some(rest) => Some(dirs::home_dir()?.join(rest.trim_start_matches('/'))),
+                None => Some(PathBuf::from(root)),
+            })
+            .collect()
+    }
+
     /// Gets alert config for a drive, returning default if not set.
-    pub fn get_drive_alert(&self, mount_point: &str) -> DriveAlertConfig {
+    pub fn get_drive_alert(&self, id: &str) -> DriveAlertConfig {
         self.drive_alerts
-            .get(mount_point)
+            .get(id)
             .cloned()
             .unwrap_or(DriveAlertConfig {
-                enabled: true,
-                threshold: self.default_alert_threshold,
+                warning_threshold: self.default_warning_threshold,
+                critical_threshold: self.default_critical_threshold,
+                ..Default::default()
             })
     }
 }
+
+/// Version 1 per-drive settings, with a single threshold.
+#[derive(serde::Deserialize)]
+struct DriveAlertConfigV1 {
+    enabled: bool,
+    threshold: u8,
+    #[serde(default)]
+    predict_full_hours: Option<u32>,
+    #[serde(default = "default_inode_threshold")]
+    inode_threshold: u8,
+}
+
+/// Version 1 settings that carry over to the current layout.
+#[derive(Default)]
+struct ConfigV1 {
+    default_alert_threshold: u8,
+    poll_interval: Option<u64>,
+    statvfs_timeout: Option<u64>,
+    monitored_drives: Option<Vec<String>>,
+    alert_cooldown: Option<u64>,
+    panel_drives: Option<Vec<String>>,
+    drive_alerts: HashMap<String, DriveAlertConfigV1>,
+}
+
+/// Lowest threshold the popup's threshold sliders offer.
+const THRESHOLD_MIN: u8 = 50;
+/// Highest threshold the popup's threshold sliders offer.
+const THRESHOLD_MAX: u8 = 99;
+/// Gap between the critical and the derived warning threshold when migrating.
+const MIGRATED_WARNING_GAP: u8 = 10;
+
+/// Derives warning and critical thresholds from a version 1 single threshold.
+///
+/// The old threshold becomes the critical level so existing alerts keep firing
+/// at the same point. Both are kept within the sliders' range.
+fn split_threshold(threshold: u8) -> (u8, u8) {
+    let critical = threshold.clamp(THRESHOLD_MIN, THRESHOLD_MAX);
+    let warning = critical.saturating_sub(MIGRATED_WARNING_GAP).max(THRESHOLD_MIN);
+    (warning, critical)
+}
+
+impl ConfigV1 {
+    /// Reads a version 1 config, if one exists.
+    fn read(app_id: &str) -> Option<Self> {
+        let old = cosmic_config::Config::new(app_id, 1).ok()?;
+
+        Some(Self {
+            default_alert_threshold: old.get("default_alert_threshold").ok()?,
+            poll_interval: old.get("poll_interval").ok(),
+            statvfs_timeout: old.get("statvfs_timeout").ok(),
+            monitored_drives: old.get("monitored_drives").ok(),
+            alert_cooldown: old.get("alert_cooldown").ok(),
+            panel_drives: old.get("panel_drives").ok(),
+            drive_alerts: old.get("drive_alerts").unwrap_or_default(),
+        })
+    }
+
+    /// Converts to the current layout, using defaults for new settings.
+    fn migrate(self) -> Config {
+        let defaults = Config::default();
+        let (default_warning_threshold, default_critical_threshold) =
+            split_threshold(self.default_alert_threshold);
+
+        let drive_alerts = self
+            .drive_alerts
+            .into_iter()
+            .map(|(mount, v1)| {
+                let (warning_threshold, critical_threshold) = split_threshold(v1.threshold);
+                let alert = DriveAlertConfig {
+                    enabled: v1.enabled,
+                    warning_threshold,
+                    critical_threshold,
+                    predict_full_hours: v1.predict_full_hours,
+                    inode_threshold: v1.inode_threshold,
+                    ..Default::default()
+                };
+                (mount, alert)
+            })
+            .collect();
+
+        Config {
+            poll_interval: self.poll_interval.unwrap_or(defaults.poll_interval),
+            statvfs_timeout: self.statvfs_timeout.unwrap_or(defaults.statvfs_timeout),
+            default_warning_threshold,
+            default_critical_threshold,
+            monitored_drives: self.monitored_drives.unwrap_or(defaults.monitored_drives),
+            alert_cooldown: self.alert_cooldown.unwrap_or(defaults.alert_cooldown),
+            hysteresis_margin: defaults.hysteresis_margin,
+            notify_recovery: defaults.notify_recovery,
+            hook_timeout: defaults.hook_timeout,
+            quiet_hours: defaults.quiet_hours,
+            temperature_limit: defaults.temperature_limit,
+            monitor_network_mounts: defaults.monitor_network_mounts,
+            metrics_path: defaults.metrics_path,
+            cleanup_roots: defaults.cleanup_roots,
+            panel_drives: self.panel_drives.unwrap_or(defaults.panel_drives),
+            drive_alerts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drive_v1(enabled: bool, threshold: u8) -> DriveAlertConfigV1 {
+        DriveAlertConfigV1 {
+            enabled,
+            threshold,
+            predict_full_hours: Some(24),
+            inode_threshold: 80,
+        }
+    }
+
+    #[test]
+    fn split_threshold_stays_in_slider_range() {
+        assert_eq!(split_threshold(90), (80, 90));
+        assert_eq!(split_threshold(55), (50, 55));
+        assert_eq!(split_threshold(30), (50, 50));
+        assert_eq!(split_threshold(100), (89, 99));
+    }
+
+    #[test]
+    fn migrates_v1_config() {
+        let v1 = ConfigV1 {
+            default_alert_threshold: 85,
+            poll_interval: Some(60),
+            panel_drives: Some(vec!["/".to_string()]),
+            drive_alerts: HashMap::from([
+                ("/".to_string(), drive_v1(true, 95)),
+                ("/mnt/backup".to_string(), drive_v1(false, 40)),
+            ]),
+            ..Default::default()
+        };
+
+        let config = v1.migrate();
+        let defaults = Config::default();
+
+        assert_eq!(config.default_warning_threshold, 75);
+        assert_eq!(config.default_critical_threshold, 85);
+        assert_eq!(config.poll_interval, 60);
+        assert_eq!(config.statvfs_timeout, defaults.statvfs_timeout);
+        assert_eq!(config.monitored_drives, defaults.monitored_drives);
+        assert_eq!(config.panel_drives, vec!["/".to_string()]);
+        assert_eq!(config.hysteresis_margin, defaults.hysteresis_margin);
+
+        let root = &config.drive_alerts["/"];
+        assert!(root.enabled);
+        assert_eq!((root.warning_threshold, root.critical_threshold), (85, 95));
+        assert_eq!(root.predict_full_hours, Some(24));
+        assert_eq!(root.inode_threshold, 80);
+        assert_eq!(root.quota_threshold, DriveAlertConfig::default().quota_threshold);
+
+        let backup = &config.drive_alerts["/mnt/backup"];
+        assert!(!backup.enabled);
+        assert_eq!((backup.warning_threshold, backup.critical_threshold), (50, 50));
+    }
+
+    #[test]
+    fn inode_level_warns_below_critical_threshold() {
+        let alert_config = DriveAlertConfig {
+            critical_threshold: 95,
+            inode_threshold: 85,
+            ..Default::default()
+        };
+
+        assert_eq!(alert_config.inode_level(84), AlertLevel::Normal);
+        assert_eq!(alert_config.inode_level(85), AlertLevel::Warning);
+        assert_eq!(alert_config.inode_level(95), AlertLevel::Critical);
+
+        // A threshold above critical goes straight to critical
+        let alert_config = DriveAlertConfig {
+            inode_threshold: 97,
+            ..alert_config
+        };
+        assert_eq!(alert_config.inode_level(96), AlertLevel::Normal);
+        assert_eq!(alert_config.inode_level(97), AlertLevel::Critical);
+    }
+
+    #[test]
+    fn held_level_applies_margin_to_min_free() {
+        let alert_config = DriveAlertConfig {
+            min_free_gib: Some(10),
+            ..Default::default()
+        };
+        let space = |available_gib: u64| SpaceInfo {
+            total: 1000 * GIB,
+            used: 100 * GIB,
+            available: available_gib * GIB,
+            ..Default::default()
+        };
+
+        assert_eq!(alert_config.level(&space(9)), AlertLevel::Critical);
+        assert_eq!(alert_config.level(&space(11)), AlertLevel::Normal);
+        assert_eq!(alert_config.held_level(&space(11), 2), AlertLevel::Critical);
+        assert_eq!(alert_config.held_level(&space(12), 2), AlertLevel::Normal);
+    }
+}
@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! On-disk usage history and fill rate prediction.
+//!
+//! Each mount point gets a small file of fixed-size samples under the XDG
+//! state directory. The file is appended to on every sample and compacted
+//! back down to [`CAPACITY`] records once it grows past that.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+use crate::space::SpaceInfo;
+
+/// Bytes per on-disk sample: timestamp, used, total as little-endian u64.
+const RECORD_SIZE: usize = 24;
+/// Minimum seconds between stored samples.
+const SAMPLE_INTERVAL: u64 = 60;
+/// Samples kept per mount point (30 days at one per minute).
+const CAPACITY: usize = 30 * 24 * 60;
+/// Extra records allowed in a history file before it is compacted.
+const COMPACT_SLACK: usize = 24 * 60;
+/// How far back the fill rate regression looks.
+const REGRESSION_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+/// Minimum number of samples before a prediction is made.
+const MIN_SAMPLES: usize = 10;
+/// Minimum time covered by the samples before a prediction is made.
+const MIN_SPAN: u64 = 15 * 60;
+
+/// A single usage sample.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Used bytes.
+    pub used: u64,
+    /// Total bytes.
+    pub total: u64,
+}
+
+impl Sample {
+    fn to_bytes(self) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0u8; RECORD_SIZE];
+        bytes[0..8].copy_from_slice(&self.timestamp.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.used.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.total.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let field = |range: std::ops::Range<usize>| {
+            u64::from_le_bytes(bytes[range].try_into().unwrap_or_default())
+        };
+        Self {
+            timestamp: field(0..8),
+            used: field(8..16),
+            total: field(16..24),
+        }
+    }
+}
+
+/// Samples for one mount point plus the number of records in its file.
+#[derive(Debug, Default)]
+struct Series {
+    samples: VecDeque<Sample>,
+    records_on_disk: usize,
+}
+
+/// Usage history for all monitored mount points.
+#[derive(Debug)]
+pub struct HistoryStore {
+    /// Directory holding the history files, if one could be determined.
+    dir: Option<PathBuf>,
+    series: HashMap<PathBuf, Series>,
+}
+
+impl HistoryStore {
+    /// Creates a store backed by `$XDG_STATE_HOME/cosmic-ext-applet-cargowatch/history`.
+    pub fn new() -> Self {
+        let dir = dirs::state_dir().map(|d| d.join("cosmic-ext-applet-cargowatch").join("history"));
+
+        if let Some(ref dir) = dir {
+            if let Err(why) = fs::create_dir_all(dir) {
+                eprintln!("failed to create history directory {}: {why}", dir.display());
+            }
+        }
+
+        Self {
+            dir,
+            series: HashMap::new(),
+        }
+    }
+
+    /// Records a sample for the mount point, rate-limited to one per [`SAMPLE_INTERVAL`].
+    pub fn record(&mut self, mount_point: &Path, space: &SpaceInfo) -> Result<()> {
+        let timestamp = unix_now();
+        let file = self.file_for(mount_point);
+        let series = self.load(mount_point);
+
+        if let Some(last) = series.samples.back() {
+            if timestamp.saturating_sub(last.timestamp) < SAMPLE_INTERVAL {
+                return Ok(());
+            }
+        }
+
+        let sample = Sample {
+            timestamp,
+            used: space.used,
+            total: space.total,
+        };
+
+        series.samples.push_back(sample);
+        while series.samples.len() > CAPACITY {
+            series.samples.pop_front();
+        }
+
+        let Some(file) = file else {
+            return Ok(());
+        };
+
+        if series.records_on_disk >= CAPACITY + COMPACT_SLACK {
+            compact(&file, &series.samples)?;
+            series.records_on_disk = series.samples.len();
+        } else {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file)
+                .and_then(|mut f| f.write_all(&sample.to_bytes()))
+                .with_context(|| format!("failed to append to {}", file.display()))?;
+            series.records_on_disk += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the samples for a mount point from the last `window`, oldest first.
+    pub fn recent(&self, mount_point: &Path, window: Duration) -> Vec<Sample> {
+        let Some(series) = self.series.get(mount_point) else {
+            return Vec::new();
+        };
+
+        let since = unix_now().saturating_sub(window.as_secs());
+        series
+            .samples
+            .iter()
+            .filter(|s| s.timestamp >= since)
+            .copied()
+            .collect()
+    }
+
+    /// Estimates how long until the drive fills up at its recent fill rate.
+    ///
+    /// Returns `None` if there is not enough history or the drive is not filling.
+    pub fn time_to_full(&self, mount_point: &Path, available: u64) -> Option<Duration> {
+        let recent = self.recent(mount_point, REGRESSION_WINDOW);
+        let rate = fill_rate(&recent)?;
+        Duration::try_from_secs_f64(available as f64 / rate).ok()
+    }
+
+    fn file_for(&self, mount_point: &Path) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.usage", escape_mount_point(mount_point))))
+    }
+
+    /// Returns the series for a mount point, reading it from disk on first use.
+    fn load(&mut self, mount_point: &Path) -> &mut Series {
+        let file = self.file_for(mount_point);
+
+        self.series.entry(mount_point.to_path_buf()).or_insert_with(|| {
+            let Some(file) = file else {
+                return Series::default();
+            };
+
+            match read_samples(&file) {
+                Ok(samples) => {
+                    let records_on_disk = samples.len();
+                    let skip = samples.len().saturating_sub(CAPACITY);
+                    Series {
+                        samples: samples.into_iter().skip(skip).collect(),
+                        records_on_disk,
+                    }
+                }
+                Err(why) => {
+                    eprintln!("failed to read usage history: {why}");
+                    Series::default()
+                }
+            }
+        })
+    }
+}
+
+/// Returns the fill rate in bytes per second using a least-squares fit of
+/// used bytes over time, or `None` if usage is flat or shrinking.
+pub fn fill_rate(samples: &[Sample]) -> Option<f64> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let first = samples.first()?.timestamp;
+    let last = samples.last()?.timestamp;
+    if last.saturating_sub(first) < MIN_SPAN {
+        return None;
+    }
+
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|s| (s.timestamp - first) as f64).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|s| s.used as f64).sum::<f64>() / n;
+
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for sample in samples {
+        let dx = (sample.timestamp - first) as f64 - mean_x;
+        let dy = sample.used as f64 - mean_y;
+        covariance += dx * dy;
+        variance += dx * dx;
+    }
+
+    if variance == 0.0 {
+        return None;
+    }
+
+    let slope = covariance / variance;
+    (slope > 0.0).then_some(slope)
+}
+
+fn read_samples(file: &Path) -> Result<Vec<Sample>> {
+    let mut bytes = Vec::new();
+    match File::open(file) {
+        Ok(mut f) => f
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("failed to read {}", file.display()))?,
+        Err(why) if why.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(why) => return Err(why).with_context(|| format!("failed to open {}", file.display())),
+    };
+
+    // A trailing partial record means an interrupted write; drop it
+    Ok(bytes.chunks_exact(RECORD_SIZE).map(Sample::from_bytes).collect())
+}
+
+/// Rewrites a history file with only the given samples.
+fn compact(file: &Path, samples: &VecDeque<Sample>) -> Result<()> {
+    let tmp = file.with_extension("usage.tmp");
+    let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_bytes()).collect();
+
+    fs::write(&tmp, bytes).with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, file).with_context(|| format!("failed to replace {}", file.display()))
+}
+
+/// Turns a mount point into a file name, escaping `%` and `/`.
+fn escape_mount_point(mount_point: &Path) -> String {
+    mount_point
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('/', "%2F")
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples one minute apart, growing by `step` bytes each.
+    fn linear(count: u64, step: i64) -> Vec<Sample> {
+        (0..count)
+            .map(|i| Sample {
+                timestamp: 1_000_000 + i * 60,
+                used: (1_000_000_000 + i as i64 * step) as u64,
+                total: 2_000_000_000,
+            })
+            .collect()
+    }
+
+    fn temp_store(name: &str) -> HistoryStore {
+        let dir = std::env::temp_dir().join(format!("cargowatch-history-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        HistoryStore {
+            dir: Some(dir),
+            series: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn fill_rate_of_steady_growth() {
+        let rate = fill_rate(&linear(30, 600)).unwrap();
+        assert!((rate - 10.0).abs() < 1e-6, "rate was {rate}");
+    }
+
+    #[test]
+    fn fill_rate_ignores_flat_and_shrinking_usage() {
+        assert_eq!(fill_rate(&linear(30, 0)), None);
+        assert_eq!(fill_rate(&linear(30, -600)), None);
+    }
+
+    #[test]
+    fn fill_rate_needs_enough_history() {
+        // Too few samples
+        assert_eq!(fill_rate(&linear(MIN_SAMPLES as u64 - 1, 600)), None);
+
+        // Enough samples, but spanning less than MIN_SPAN
+        let mut samples = linear(MIN_SAMPLES as u64 * 2, 600);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            sample.timestamp = 1_000_000 + i as u64;
+        }
+        assert_eq!(fill_rate(&samples), None);
+    }
+
+    #[test]
+    fn escapes_mount_points() {
+        assert_eq!(escape_mount_point(Path::new("/")), "%2F");
+        assert_eq!(escape_mount_point(Path::new("/mnt/data")), "%2Fmnt%2Fdata");
+        // An escaped percent can't collide with an escaped slash
+        assert_eq!(escape_mount_point(Path::new("/mnt/a%2Fb")), "%2Fmnt%2Fa%252Fb");
+    }
+
+    #[test]
+    fn drops_partial_records() {
+        let store = temp_store("partial");
+        let file = store.file_for(Path::new("/")).unwrap();
+        let mut bytes: Vec<u8> = linear(3, 1).iter().flat_map(|s| s.to_bytes()).collect();
+        bytes.extend_from_slice(&[0; RECORD_SIZE / 2]);
+        fs::write(&file, bytes).unwrap();
+
+        let samples = read_samples(&file).unwrap();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[2].used, 1_000_000_002);
+    }
+
+    #[test]
+    fn compacts_oversized_history() {
+        let mut store = temp_store("compact");
+        let mount_point = Path::new("/");
+        let file = store.file_for(mount_point).unwrap();
+
+        // Old enough that the next sample isn't rate limited
+        let records = CAPACITY + COMPACT_SLACK;
+        let bytes: Vec<u8> = linear(records as u64, 1).iter().flat_map(|s| s.to_bytes()).collect();
+        fs::write(&file, bytes).unwrap();
+
+        let space = SpaceInfo {
+            used: 42,
+            total: 100,
+            ..Default::default()
+        };
+        store.record(mount_point, &space).unwrap();
+
+        let samples = read_samples(&file).unwrap();
+        assert_eq!(samples.len(), CAPACITY);
+        assert_eq!(samples.last().unwrap().used, 42);
+        assert_eq!(store.series[mount_point].records_on_disk, CAPACITY);
+
+        let _ = fs::remove_dir_all(store.dir.unwrap());
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! User-defined commands run on usage threshold events.
+//!
+//! Each command runs through `sh -c` on its own thread with details of the
+//! event in environment variables:
+//!
+//! - `CARGOWATCH_EVENT`: `crossed`, `still-over` or `recovered`
+//! - `CARGOWATCH_LEVEL`: `normal`, `warning` or `critical`
+//! - `CARGOWATCH_DRIVE`: the drive's display name, or the ZFS pool name
+//! - `CARGOWATCH_MOUNT_POINT`: the mount point, empty for ZFS pools
+//! - `CARGOWATCH_PERCENT`: usage percentage
+//! - `CARGOWATCH_FREE_BYTES`: bytes available
+
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+
+use crate::config::AlertLevel;
+
+/// How often a running hook is checked for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Usage threshold events a hook can run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// Usage reached the warning or critical threshold.
+    Crossed,
+    /// Usage is still over a threshold after the alert cooldown.
+    StillOver,
+    /// Usage dropped back below the warning threshold.
+    Recovered,
+}
+
+impl HookEvent {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Crossed => "crossed",
+            Self::StillOver => "still-over",
+            Self::Recovered => "recovered",
+        }
+    }
+}
+
+/// The drive or pool an event is about.
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub drive: String,
+    pub mount_point: Option<PathBuf>,
+    pub level: AlertLevel,
+    pub percent: u8,
+    pub free: u64,
+}
+
+/// Runs `command` in the background, killing it after `timeout`.
+///
+/// The exit status is logged; nothing waits on the result.
+pub fn run(command: String, event: HookEvent, context: HookContext, timeout: Duration) {
+    let spawned = thread::Builder::new()
+        .name("cargowatch-hook".to_string())
+        .spawn(move || run_blocking(&command, event, &context, timeout));
+
+    if let Err(why) = spawned {
+        eprintln!("failed to start hook thread: {why}");
+    }
+}
+
+fn run_blocking(command: &str, event: HookEvent, context: &HookContext, timeout: Duration) {
+    let mount_point = context
+        .mount_point
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("CARGOWATCH_EVENT", event.as_str())
+        .env("CARGOWATCH_LEVEL", context.level.as_str())
+        .env("CARGOWATCH_DRIVE", &context.drive)
+        .env("CARGOWATCH_MOUNT_POINT", mount_point)
+        .env("CARGOWATCH_PERCENT", context.percent.to_string())
+        .env("CARGOWATCH_FREE_BYTES", context.free.to_string())
+        .stdin(Stdio::null())
+        // Own process group, so a timeout also kills whatever the shell started
+        .process_group(0)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(why) => {
+            eprintln!("failed to run {} hook for {}: {why}", event.as_str(), context.drive);
+            return;
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                eprintln!("{} hook for {} exited with {status}", event.as_str(), context.drive);
+                return;
+            }
+            Ok(None) if Instant::now() >= deadline => {
+                eprintln!(
+                    "{} hook for {} timed out after {}s, killing it",
+                    event.as_str(),
+                    context.drive,
+                    timeout.as_secs()
+                );
+                if let Err(why) = killpg(Pid::from_raw(child.id() as i32), Signal::SIGKILL) {
+                    eprintln!("failed to kill {} hook for {}: {why}", event.as_str(), context.drive);
+                }
+                // Reap it too, so it doesn't linger as a zombie
+                let _ = child.wait();
+                return;
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(why) => {
+                eprintln!("failed to wait for {} hook for {}: {why}", event.as_str(), context.drive);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Local wall-clock time, for quiet hours.
+
+use std::time::Duration;
+
+use nix::libc;
+
+/// Seconds in a day, ignoring DST changes.
+const DAY_SECS: u32 = 24 * 60 * 60;
+
+/// Returns seconds since local midnight, using the system time zone.
+pub fn seconds_since_midnight() -> Option<u32> {
+    // SAFETY: `time` accepts a null pointer and only returns the current time
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    // SAFETY: `struct tm` is plain integers and a pointer, so all zeroes is a valid value
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers are valid for the duration of the call, and
+    // localtime_r writes only to `tm`
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return None;
+    }
+
+    Some((tm.tm_hour * 3600 + tm.tm_min * 60 + tm.tm_sec) as u32)
+}
+
+/// Returns the minute of the local day (0-1439).
+pub fn minute_of_day() -> Option<u16> {
+    seconds_since_midnight().map(|secs| (secs / 60) as u16)
+}
+
+/// Returns the time left until the next local midnight.
+pub fn until_midnight() -> Option<Duration> {
+    seconds_since_midnight().map(|secs| Duration::from_secs(u64::from(DAY_SECS.saturating_sub(secs))))
+}
@@ -1,12 +1,30 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+mod analyzer;
 mod applet;
+mod btrfs;
+mod cleanup;
+mod cli;
 mod config;
+mod history;
+mod hooks;
 mod i18n;
+mod localtime;
+mod metrics;
+mod mountinfo;
+mod notifications;
+mod service;
 mod space;
 mod udisks;
+mod zfs;
 
 fn main() -> cosmic::iced::Result {
+    // Subcommands run headless, without starting the applet
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     // Get the system's preferred languages.
     let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
 
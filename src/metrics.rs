@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Exports drive usage as a metrics file for node_exporter's textfile collector.
+//!
+//! The file is written on its own thread, to a temporary name that is then
+//! renamed into place, so a scrape never reads a half-written file.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use anyhow::{Context, Result};
+
+use crate::space::SpaceInfo;
+
+/// One drive's values for the metrics file.
+pub struct DriveMetrics<'a> {
+    pub mount_point: &'a Path,
+    pub device: &'a str,
+    pub fs_type: &'a str,
+    pub space: &'a SpaceInfo,
+    pub warning_threshold: u8,
+    pub critical_threshold: u8,
+    /// True if the drive is at or above its warning level.
+    pub alert_active: bool,
+}
+
+/// Renders drives in the OpenMetrics text format.
+pub fn render(drives: &[DriveMetrics]) -> String {
+    let mut out = String::new();
+
+    bytes_gauge(&mut out, drives, "cargowatch_bytes_total", "Size of the filesystem in bytes.", |s| s.total);
+    bytes_gauge(&mut out, drives, "cargowatch_bytes_used", "Used bytes on the filesystem.", |s| s.used);
+    bytes_gauge(
+        &mut out,
+        drives,
+        "cargowatch_bytes_available",
+        "Bytes available to unprivileged users.",
+        |s| s.available,
+    );
+
+    header(&mut out, "cargowatch_threshold_percent", "Configured usage alert threshold in percent.");
+    for drive in drives {
+        for (level, threshold) in [("warning", drive.warning_threshold), ("critical", drive.critical_threshold)] {
+            let _ = writeln!(
+                out,
+                "cargowatch_threshold_percent{{{},level=\"{level}\"}} {threshold}",
+                labels(drive)
+            );
+        }
+    }
+
+    header(&mut out, "cargowatch_alert_active", "1 if the drive is at or above its warning level.");
+    for drive in drives {
+        let _ = writeln!(out, "cargowatch_alert_active{{{}}} {}", labels(drive), u8::from(drive.alert_active));
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+/// Set while a metrics file write is running.
+static IN_FLIGHT: AtomicBool = AtomicBool::new(false);
+
+/// Writes `contents` to the metrics file at `path` in the background.
+///
+/// Skipped if the previous write is still running, so a slow or hung
+/// filesystem never has more than one write waiting on it.
+pub fn write_in_background(path: PathBuf, contents: String) {
+    if IN_FLIGHT.swap(true, Ordering::AcqRel) {
+        eprintln!("previous metrics write to {} is still running, skipping", path.display());
+        return;
+    }
+
+    let spawned = thread::Builder::new()
+        .name("cargowatch-metrics".to_string())
+        .spawn(move || {
+            if let Err(why) = write(&path, &contents) {
+                eprintln!("failed to write metrics: {why:#}");
+            }
+            IN_FLIGHT.store(false, Ordering::Release);
+        });
+
+    if let Err(why) = spawned {
+        eprintln!("failed to start metrics thread: {why}");
+        IN_FLIGHT.store(false, Ordering::Release);
+    }
+}
+
+/// Writes the metrics file at `path`, replacing it atomically.
+fn write(path: &Path, contents: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("{} is not a file path", path.display()))?;
+
+    // Same directory, so the rename never crosses filesystems. The textfile
+    // collector only reads *.prom, so it skips the temporary file.
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = fs::File::create(&tmp_path)
+        .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+
+    fs::rename(&tmp_path, path).with_context(|| format!("failed to rename {} into place", tmp_path.display()))
+}
+
+fn bytes_gauge(out: &mut String, drives: &[DriveMetrics], name: &str, help: &str, value: fn(&SpaceInfo) -> u64) {
+    header(out, name, help);
+    for drive in drives {
+        let _ = writeln!(out, "{name}{{{}}} {}", labels(drive), value(drive.space));
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+}
+
+fn labels(drive: &DriveMetrics) -> String {
+    format!(
+        "mountpoint=\"{}\",device=\"{}\",fstype=\"{}\"",
+        escape(&drive.mount_point.display().to_string()),
+        escape(drive.device),
+        escape(drive.fs_type)
+    )
+}
+
+/// Escapes a label value: backslash, double quote and newline.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Drive enumeration from `/proc/self/mountinfo` and sysfs.
+//!
+//! Used as a fallback when the system bus or the UDisks2 daemon is
+//! unavailable, such as in containers or minimal installs, and for network
+//! mounts and ZFS datasets, which UDisks2 does not report. Drives found this way have no
+//! UDisks2 objects, so mount actions and SMART health are not available for them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::udisks::{self, DriveInfo};
+
+const MOUNTINFO: &str = "/proc/self/mountinfo";
+
+/// Network and FUSE filesystem types shown when network monitoring is enabled.
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.s3fs",
+    "fuse.gvfsd-fuse",
+    "9p",
+];
+
+/// A parsed line of `/proc/self/mountinfo`.
+#[derive(Debug)]
+struct MountEntry {
+    /// `major:minor` of the backing device.
+    dev: String,
+    mount_point: PathBuf,
+    fs_type: String,
+    source: String,
+}
+
+/// Enumerates mounted block device filesystems from `/proc/self/mountinfo`.
+///
+/// Applies the same filtering, deduplication and id assignment as the UDisks2
+/// backend, so settings keep applying when switching between the two.
+pub fn enumerate_drives() -> Result<Vec<DriveInfo>> {
+    let entries = read_mountinfo()?;
+
+    let uuids = read_disk_links("/dev/disk/by-uuid");
+    let part_uuids = read_disk_links("/dev/disk/by-partuuid");
+    let labels = read_disk_links("/dev/disk/by-label");
+
+    let mut drives: Vec<DriveInfo> = entries
+        .into_iter()
+        .filter(|entry| entry.source.starts_with("/dev/"))
+        .filter(|entry| !udisks::is_excluded_fs_type(&entry.fs_type))
+        .map(|entry| {
+            let canonical = fs::canonicalize(&entry.source).unwrap_or_else(|_| PathBuf::from(&entry.source));
+            let (model, removable) = read_block_info(&entry.dev);
+
+            DriveInfo {
+                mount_point: entry.mount_point,
+                label: labels.get(&canonical).cloned(),
+                device: entry.source,
+                fs_type: entry.fs_type,
+                model,
+                removable,
+                system: false,
+                uuid: uuids.get(&canonical).cloned(),
+                part_uuid: part_uuids.get(&canonical).cloned(),
+                id: String::new(),
+                object_path: None,
+                drive_path: None,
+                ejectable: false,
+                can_power_off: false,
+                health: None,
+                network: false,
+                also_mounted_at: Vec::new(),
+            }
+        })
+        .collect();
+
+    udisks::deduplicate_by_device(&mut drives);
+    udisks::assign_ids(&mut drives);
+
+    Ok(drives)
+}
+
+/// Enumerates mounted network and FUSE filesystems (NFS, CIFS, 9p, and
+/// sshfs, rclone, s3fs and GVfs FUSE mounts).
+///
+/// The server and share (e.g. `server:/export` or `//server/share`) is used
+/// as the label, and the mount point as the settings id.
+pub fn network_mounts() -> Result<Vec<DriveInfo>> {
+    let mut drives: Vec<DriveInfo> = read_mountinfo()?
+        .into_iter()
+        .filter(|entry| NETWORK_FS_TYPES.contains(&entry.fs_type.as_str()))
+        .map(|entry| DriveInfo {
+            mount_point: entry.mount_point,
+            label: Some(entry.source.clone()),
+            device: entry.source,
+            fs_type: entry.fs_type,
+            model: None,
+            removable: false,
+            system: false,
+            uuid: None,
+            part_uuid: None,
+            id: String::new(),
+            object_path: None,
+            drive_path: None,
+            ejectable: false,
+            can_power_off: false,
+            health: None,
+            network: true,
+            also_mounted_at: Vec::new(),
+        })
+        .collect();
+
+    udisks::assign_ids(&mut drives);
+
+    Ok(drives)
+}
+
+/// Enumerates mounted ZFS datasets, with the dataset name as the device.
+///
+/// Automounted snapshots are skipped.
+pub fn zfs_datasets() -> Result<Vec<DriveInfo>> {
+    let mut drives: Vec<DriveInfo> = read_mountinfo()?
+        .into_iter()
+        .filter(|entry| entry.fs_type == "zfs" && !entry.source.contains('@'))
+        .map(|entry| DriveInfo {
+            mount_point: entry.mount_point,
+            label: None,
+            device: entry.source,
+            fs_type: entry.fs_type,
+            model: None,
+            removable: false,
+            system: false,
+            uuid: None,
+            part_uuid: None,
+            id: String::new(),
+            object_path: None,
+            drive_path: None,
+            ejectable: false,
+            can_power_off: false,
+            health: None,
+            network: false,
+            also_mounted_at: Vec::new(),
+        })
+        .collect();
+
+    udisks::assign_ids(&mut drives);
+
+    Ok(drives)
+}
+
+fn read_mountinfo() -> Result<Vec<MountEntry>> {
+    let contents = fs::read_to_string(MOUNTINFO).with_context(|| format!("failed to read {MOUNTINFO}"))?;
+    Ok(contents.lines().filter_map(parse_line).collect())
+}
+
+/// Parses one mountinfo line.
+///
+/// The format is `id parent major:minor root mount_point options [optional...] - fs_type source super_options`.
+fn parse_line(line: &str) -> Option<MountEntry> {
+    let (before, after) = line.split_once(" - ")?;
+
+    let mut fields = before.split(' ');
+    let dev = fields.nth(2)?.to_string();
+    let mount_point = PathBuf::from(unescape_octal(fields.nth(1)?));
+
+    let mut fields = after.split(' ');
+    let fs_type = fields.next()?.to_string();
+    let source = unescape_octal(fields.next()?);
+
+    Some(MountEntry {
+        dev,
+        mount_point,
+        fs_type,
+        source,
+    })
+}
+
+/// Decodes the `\ooo` octal escapes the kernel uses for spaces and other
+/// special characters in mountinfo paths.
+fn unescape_octal(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 4 <= bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 4])
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+            {
+                out.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Maps each device a `/dev/disk/by-*` directory links to onto its link name.
+///
+/// udev escapes unsafe characters in link names as `\xHH`.
+fn read_disk_links(dir: &str) -> HashMap<PathBuf, String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let device = fs::canonicalize(entry.path()).ok()?;
+            let name = unescape_hex(&entry.file_name().to_string_lossy());
+            Some((device, name))
+        })
+        .collect()
+}
+
+/// Decodes udev's `\xHH` escapes.
+fn unescape_hex(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"\\x") && i + 4 <= bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 2..i + 4])
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            {
+                out.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Reads the model and removable flag of the disk holding a block device.
+///
+/// Partitions are resolved to their parent disk, whose attributes live under
+/// `/sys/block/<disk>`.
+fn read_block_info(dev: &str) -> (Option<String>, bool) {
+    let Ok(sys_path) = fs::canonicalize(Path::new("/sys/dev/block").join(dev)) else {
+        return (None, false);
+    };
+
+    let disk_path = match sys_path.parent() {
+        Some(parent) if sys_path.join("partition").exists() => parent,
+        _ => &sys_path,
+    };
+
+    let Some(disk) = disk_path.file_name() else {
+        return (None, false);
+    };
+    let block = Path::new("/sys/block").join(disk);
+
+    let model = fs::read_to_string(block.join("device/model"))
+        .ok()
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+    let removable = fs::read_to_string(block.join("removable")).is_ok_and(|flag| flag.trim() == "1");
+
+    (model, removable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mountinfo_line() {
+        let entry = parse_line("36 35 98:0 / /mnt/data rw,noatime master:1 - ext4 /dev/sda1 rw,errors=continue").unwrap();
+        assert_eq!(entry.dev, "98:0");
+        assert_eq!(entry.mount_point, Path::new("/mnt/data"));
+        assert_eq!(entry.fs_type, "ext4");
+        assert_eq!(entry.source, "/dev/sda1");
+    }
+
+    #[test]
+    fn parses_any_number_of_optional_fields() {
+        let none = parse_line("22 1 0:21 / /proc rw,nosuid - proc proc rw").unwrap();
+        assert_eq!(none.mount_point, Path::new("/proc"));
+        assert_eq!(none.fs_type, "proc");
+
+        let several = parse_line("40 22 0:35 / /srv rw shared:5 master:2 propagate_from:1 - nfs4 server:/export rw").unwrap();
+        assert_eq!(several.mount_point, Path::new("/srv"));
+        assert_eq!(several.fs_type, "nfs4");
+        assert_eq!(several.source, "server:/export");
+    }
+
+    #[test]
+    fn parses_escaped_mount_point() {
+        let entry = parse_line("50 22 8:17 / /media/My\\040Disk rw shared:9 - vfat /dev/sdb1 rw").unwrap();
+        assert_eq!(entry.mount_point, Path::new("/media/My Disk"));
+    }
+
+    #[test]
+    fn rejects_line_without_separator() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("36 35 98:0 / /mnt rw ext4 /dev/sda1 rw").is_none());
+    }
+
+    #[test]
+    fn unescapes_octal() {
+        assert_eq!(unescape_octal("/mnt/a\\040b"), "/mnt/a b");
+        assert_eq!(unescape_octal("tab\\011and\\134slash"), "tab\tand\\slash");
+        // Incomplete or non-octal escapes are kept as they are
+        assert_eq!(unescape_octal("end\\04"), "end\\04");
+        assert_eq!(unescape_octal("bad\\089"), "bad\\089");
+    }
+
+    #[test]
+    fn unescapes_hex() {
+        assert_eq!(unescape_hex("My\\x20Disk"), "My Disk");
+        assert_eq!(unescape_hex("a\\x2fb"), "a/b");
+        assert_eq!(unescape_hex("end\\x2"), "end\\x2");
+        assert_eq!(unescape_hex("bad\\xzz"), "bad\\xzz");
+    }
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Alert notifications with action buttons.
+//!
+//! Alerts are sent and their `ActionInvoked`/`NotificationClosed` signals
+//! received on one long-lived session connection, so nothing blocks waiting
+//! on a notification. Some servers send those signals only to the connection
+//! that created the notification, which is why sending goes through here too.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use futures_util::stream::{select_all, SelectAll};
+use futures_util::StreamExt;
+use notify_rust::Urgency;
+use zbus::message::Type as MessageType;
+use zbus::zvariant::Value;
+use zbus::{Connection, MatchRule, MessageStream};
+
+const NOTIFICATIONS_DEST: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+const NOTIFICATIONS_IFACE: &str = "org.freedesktop.Notifications";
+
+/// Something that happened to a notification, by notification id.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// An action button was pressed.
+    Action(u32, String),
+    /// The notification expired or was dismissed.
+    Closed(u32),
+}
+
+/// Session bus connection that reports notification actions.
+pub struct ActionWatcher {
+    connection: Connection,
+    signals: SelectAll<MessageStream>,
+}
+
+impl ActionWatcher {
+    /// Connects to the session bus and subscribes to notification signals.
+    pub async fn new() -> Result<Self> {
+        let connection = Connection::session()
+            .await
+            .context("failed to connect to session D-Bus")?;
+
+        let rule = |member: &'static str| -> Result<MatchRule<'static>> {
+            Ok(MatchRule::builder()
+                .msg_type(MessageType::Signal)
+                .path(NOTIFICATIONS_PATH)?
+                .interface(NOTIFICATIONS_IFACE)?
+                .member(member)?
+                .build())
+        };
+
+        let mut streams = Vec::new();
+        for rule in [rule("ActionInvoked")?, rule("NotificationClosed")?] {
+            let stream = MessageStream::for_match_rule(rule, &connection, None)
+                .await
+                .context("failed to subscribe to notification signals")?;
+            streams.push(stream);
+        }
+
+        Ok(Self {
+            connection,
+            signals: select_all(streams),
+        })
+    }
+
+    /// The connection to pass to [`show`].
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Waits for the next action or close signal.
+    ///
+    /// Signals for other applications' notifications are included; callers
+    /// match the id against the notifications they sent.
+    pub async fn next(&mut self) -> Result<Event> {
+        while let Some(message) = self.signals.next().await {
+            let message = message.context("failed to receive notification signal")?;
+            let header = message.header();
+            let body = message.body();
+
+            let event = match header.member().map(|m| m.as_str()) {
+                Some("ActionInvoked") => body
+                    .deserialize::<(u32, String)>()
+                    .ok()
+                    .map(|(id, action)| Event::Action(id, action)),
+                Some("NotificationClosed") => body.deserialize::<(u32, u32)>().ok().map(|(id, _)| Event::Closed(id)),
+                _ => None,
+            };
+            if let Some(event) = event {
+                return Ok(event);
+            }
+        }
+
+        anyhow::bail!("notification signal stream closed")
+    }
+}
+
+/// Shows a notification with `(key, label)` action buttons and returns its id.
+pub async fn show(
+    connection: &Connection,
+    summary: &str,
+    body: &str,
+    urgency: Urgency,
+    actions: &[(&str, String)],
+) -> Result<u32> {
+    let actions: Vec<&str> = actions
+        .iter()
+        .flat_map(|(key, label)| [*key, label.as_str()])
+        .collect();
+
+    let urgency: u8 = match urgency {
+        Urgency::Low => 0,
+        Urgency::Normal => 1,
+        Urgency::Critical => 2,
+    };
+    let hints = HashMap::from([("urgency", Value::from(urgency))]);
+
+    let reply = connection
+        .call_method(
+            Some(NOTIFICATIONS_DEST),
+            NOTIFICATIONS_PATH,
+            Some(NOTIFICATIONS_IFACE),
+            "Notify",
+            // replaces_id 0 and expire_timeout -1 leave both to the server
+            &(env!("CARGO_PKG_NAME"), 0u32, "drive-harddisk", summary, body, actions, hints, -1i32),
+        )
+        .await
+        .context("Notify call failed")?;
+
+    reply.body().deserialize().context("invalid Notify reply")
+}
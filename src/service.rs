@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Session D-Bus service that lets scripts and other tools read drive status.
+//!
+//! The applet owns `com.vintagetechie.CargoWatch` and serves the
+//! `com.vintagetechie.CargoWatch1` interface at `/com/vintagetechie/CargoWatch`.
+//! For example:
+//!
+//! ```sh
+//! busctl --user call com.vintagetechie.CargoWatch /com/vintagetechie/CargoWatch \
+//!     com.vintagetechie.CargoWatch1 GetDrives
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::Type;
+use zbus::{fdo, Connection};
+
+/// Well-known name owned on the session bus.
+pub const BUS_NAME: &str = "com.vintagetechie.CargoWatch";
+/// Object path the interface is served at.
+pub const OBJECT_PATH: &str = "/com/vintagetechie/CargoWatch";
+
+/// One monitored drive as returned by `GetDrives`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DriveSnapshot {
+    /// Stable drive id, accepted by `Snooze`.
+    pub id: String,
+    pub mount_point: String,
+    pub label: String,
+    pub device: String,
+    pub fs_type: String,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub percent: u8,
+}
+
+/// The latest drive snapshots, updated by the applet on every refresh.
+pub type SharedDrives = Arc<Mutex<Vec<DriveSnapshot>>>;
+
+/// Requests from D-Bus clients for the applet to act on.
+#[derive(Debug, Clone)]
+pub enum Request {
+    /// Re-read disk space now instead of waiting for the next poll.
+    Refresh,
+    /// Suppress alerts for a drive id, or every drive if `None`, until the given time.
+    Snooze(Option<String>, Instant),
+}
+
+struct Service {
+    drives: SharedDrives,
+    requests: mpsc::Sender<Request>,
+}
+
+impl Service {
+    fn snapshot(&self) -> Vec<DriveSnapshot> {
+        self.drives.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    async fn send(&self, request: Request) -> fdo::Result<()> {
+        self.requests
+            .send(request)
+            .await
+            .map_err(|_| fdo::Error::Failed("CargoWatch is shutting down".to_string()))
+    }
+}
+
+#[zbus::interface(name = "com.vintagetechie.CargoWatch1")]
+impl Service {
+    /// Returns every monitored drive as
+    /// (id, mount point, label, device, fs type, total, used, available, percent).
+    fn get_drives(&self) -> Vec<DriveSnapshot> {
+        self.snapshot()
+    }
+
+    /// Refreshes disk space immediately.
+    async fn refresh(&self) -> fdo::Result<()> {
+        self.send(Request::Refresh).await
+    }
+
+    /// Snoozes alerts for `seconds`. `drive` is a drive id or mount point,
+    /// or empty for every drive.
+    async fn snooze(&self, drive: &str, seconds: u64) -> fdo::Result<()> {
+        let id = if drive.is_empty() {
+            None
+        } else {
+            let id = self
+                .snapshot()
+                .into_iter()
+                .find(|d| d.id == drive || d.mount_point == drive)
+                .map(|d| d.id)
+                .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown drive {drive}")))?;
+            Some(id)
+        };
+
+        let until = Instant::now()
+            .checked_add(Duration::from_secs(seconds))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("{seconds} seconds is too long to snooze")))?;
+
+        self.send(Request::Snooze(id, until)).await
+    }
+
+    /// Emitted when a drive's usage level changes. `level` is one of
+    /// "normal", "warning" or "critical".
+    #[zbus(signal)]
+    async fn threshold_crossed(
+        emitter: &SignalEmitter<'_>,
+        drive: &str,
+        mount_point: &str,
+        level: &str,
+        percent: u8,
+    ) -> zbus::Result<()>;
+}
+
+/// Connects to the session bus, serves the interface and claims [`BUS_NAME`].
+///
+/// Method calls that need the applet to act are sent to `requests`.
+pub async fn start(drives: SharedDrives, requests: mpsc::Sender<Request>) -> Result<Connection> {
+    let service = Service { drives, requests };
+
+    zbus::connection::Builder::session()
+        .context("failed to connect to the session bus")?
+        .name(BUS_NAME)
+        .context("invalid bus name")?
+        .serve_at(OBJECT_PATH, service)
+        .context("failed to serve the CargoWatch interface")?
+        .build()
+        .await
+        .with_context(|| format!("failed to own {BUS_NAME}"))
+}
+
+/// Emits `ThresholdCrossed` in the background.
+pub fn emit_threshold_crossed(connection: &Connection, drive: String, mount_point: String, level: &'static str, percent: u8) {
+    let task_connection = connection.clone();
+    connection
+        .executor()
+        .spawn(
+            async move {
+                let emitter = match SignalEmitter::new(&task_connection, OBJECT_PATH) {
+                    Ok(emitter) => emitter,
+                    Err(why) => {
+                        eprintln!("failed to emit ThresholdCrossed: {why}");
+                        return;
+                    }
+                };
+                if let Err(why) = Service::threshold_crossed(&emitter, &drive, &mount_point, level, percent).await {
+                    eprintln!("failed to emit ThresholdCrossed: {why}");
+                }
+            },
+            "threshold-crossed",
+        )
+        .detach();
+}
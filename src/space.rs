@@ -1,22 +1,35 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-//! Disk space calculations using statvfs.
+//! Disk space calculations using statvfs, and per-user quotas using quotactl.
 
-use std::path::Path;
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use nix::libc;
+use nix::sys::quota::{quotactl_get, QuotaType};
 use nix::sys::statvfs::statvfs;
+use nix::unistd::getuid;
 
 /// Disk space information for a single mount point.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SpaceInfo {
     /// Total bytes on the filesystem.
     pub total: u64,
     /// Used bytes.
     pub used: u64,
     /// Available bytes (may differ from total - used due to reserved blocks).
-    #[allow(dead_code)]
     pub available: u64,
+    /// Total inodes, or 0 if the filesystem does not report a fixed inode count.
+    pub inodes_total: u64,
+    /// Used inodes.
+    pub inodes_used: u64,
 }
 
 impl SpaceInfo {
@@ -27,6 +40,19 @@ impl SpaceInfo {
         }
         ((self.used as f64 / self.total as f64) * 100.0).round() as u8
     }
+
+    /// Returns true if the filesystem has a fixed inode table that can run out.
+    pub fn has_inodes(&self) -> bool {
+        self.inodes_total > 0
+    }
+
+    /// Returns inode usage as a percentage (0-100).
+    pub fn inodes_percent_used(&self) -> u8 {
+        if self.inodes_total == 0 {
+            return 0;
+        }
+        ((self.inodes_used as f64 / self.inodes_total as f64) * 100.0).round() as u8
+    }
 }
 
 /// Queries disk space for the given mount point.
@@ -42,13 +68,204 @@ pub fn get_space_info(mount_point: &Path) -> Result<SpaceInfo> {
     // Used = total - free (not available, since available excludes reserved blocks)
     let used = total.saturating_sub(free);
 
+    // Filesystems with dynamic inode allocation (btrfs, xfs on some setups) report 0
+    let inodes_total = stat.files() as u64;
+    let inodes_used = inodes_total.saturating_sub(stat.files_free() as u64);
+
     Ok(SpaceInfo {
         total,
         used,
         available,
+        inodes_total,
+        inodes_used,
     })
 }
 
+/// Bytes per block in quota limits (`QIF_DQBLKSIZE`).
+const QUOTA_BLOCK_SIZE: u64 = 1024;
+/// Quota type for project quotas, which nix does not expose.
+const PRJQUOTA: libc::c_int = 2;
+
+/// The current user's disk quota on one filesystem.
+#[derive(Debug, Clone, Default)]
+pub struct QuotaInfo {
+    /// Bytes charged to the quota.
+    pub used: u64,
+    /// Byte limit: the soft limit if one is set, otherwise the hard limit.
+    pub limit: u64,
+    /// True for a project quota on the user's home directory rather than a user quota.
+    pub project: bool,
+}
+
+impl QuotaInfo {
+    /// Returns quota usage as a percentage (0-100), capped at 100.
+    pub fn percent_used(&self) -> u8 {
+        if self.limit == 0 {
+            return 0;
+        }
+        ((self.used as f64 / self.limit as f64) * 100.0).round().min(100.0) as u8
+    }
+
+    /// Builds quota info from block limits, or `None` if no limit is set.
+    fn from_limits(used: u64, soft_blocks: u64, hard_blocks: u64, project: bool) -> Option<Self> {
+        let blocks = if soft_blocks > 0 { soft_blocks } else { hard_blocks };
+        (blocks > 0).then(|| Self {
+            used,
+            limit: blocks * QUOTA_BLOCK_SIZE,
+            project,
+        })
+    }
+}
+
+/// Queries the current user's quota on a filesystem.
+///
+/// Tries the user quota first (usrquota), then the project quota of the
+/// user's home directory if it lives on this filesystem (prjquota). Returns
+/// `None` if quotas are not enabled or no limit applies.
+pub fn get_quota_info(device: &str, mount_point: &Path) -> Option<QuotaInfo> {
+    if let Ok(dqblk) = quotactl_get(QuotaType::USRQUOTA, device, getuid().as_raw() as libc::c_int) {
+        let quota = QuotaInfo::from_limits(
+            dqblk.occupied_space().unwrap_or(0),
+            dqblk.blocks_soft_limit().unwrap_or(0),
+            dqblk.blocks_hard_limit().unwrap_or(0),
+            false,
+        );
+        if quota.is_some() {
+            return quota;
+        }
+    }
+
+    get_project_quota(device, mount_point)
+}
+
+/// `struct fsxattr` from `linux/fs.h`.
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct FsXattr {
+    xflags: u32,
+    extsize: u32,
+    nextents: u32,
+    projid: u32,
+    cowextsize: u32,
+    pad: [u8; 8],
+}
+
+nix::ioctl_read!(fs_ioc_fsgetxattr, b'X', 31, FsXattr);
+
+/// Queries the project quota covering the user's home directory on this filesystem.
+fn get_project_quota(device: &str, mount_point: &Path) -> Option<QuotaInfo> {
+    let home = dirs::home_dir()?;
+
+    // Only if the home directory is on this filesystem, not a nested mount
+    if home.metadata().ok()?.dev() != mount_point.metadata().ok()?.dev() {
+        return None;
+    }
+
+    let dir = File::open(&home).ok()?;
+    let mut attr = FsXattr::default();
+    // SAFETY: `attr` is a valid `struct fsxattr` for the kernel to fill in
+    unsafe { fs_ioc_fsgetxattr(dir.as_raw_fd(), &mut attr) }.ok()?;
+    if attr.projid == 0 {
+        return None;
+    }
+
+    let special = CString::new(device).ok()?;
+    // SAFETY: `struct dqblk` is plain integers, so all zeroes is a valid value
+    let mut dqblk: libc::dqblk = unsafe { std::mem::zeroed() };
+    // SAFETY: `special` is a NUL-terminated path and `dqblk` is a valid
+    // `struct dqblk` for Q_GETQUOTA to fill in
+    let ret = unsafe {
+        libc::quotactl(
+            libc::QCMD(libc::Q_GETQUOTA, PRJQUOTA),
+            special.as_ptr(),
+            attr.projid as libc::c_int,
+            (&mut dqblk as *mut libc::dqblk).cast(),
+        )
+    };
+    if ret != 0 {
+        return None;
+    }
+
+    QuotaInfo::from_limits(dqblk.dqb_curspace, dqblk.dqb_bsoftlimit, dqblk.dqb_bhardlimit, true)
+}
+
+/// Mount points with a statvfs still running on a worker thread.
+static IN_FLIGHT: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Default::default);
+/// Mount points with a quota query still running on a worker thread.
+static QUOTA_IN_FLIGHT: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Default::default);
+
+/// Queries disk space on a blocking worker thread so a slow mount
+/// cannot stall the caller's executor.
+///
+/// Returns `Ok(None)` if the filesystem does not answer within `timeout`, or
+/// if an earlier query for the same mount point is still hung.
+pub async fn get_space_info_async(mount_point: PathBuf, timeout: Duration) -> Result<Option<SpaceInfo>> {
+    let result = run_guarded(&IN_FLIGHT, mount_point, timeout, get_space_info)
+        .await
+        .context("statvfs worker panicked")?;
+    result.transpose()
+}
+
+/// Queries the current user's quota on a blocking worker thread, like
+/// [`get_space_info_async`], since quotactl and the project ID ioctl can
+/// hang on a stuck filesystem too.
+///
+/// Returns `None` if no quota applies, the query does not answer within
+/// `timeout`, or an earlier query for the same mount point is still hung.
+pub async fn get_quota_info_async(device: String, mount_point: PathBuf, timeout: Duration) -> Option<QuotaInfo> {
+    run_guarded(&QUOTA_IN_FLIGHT, mount_point, timeout, move |mount_point| {
+        get_quota_info(&device, mount_point)
+    })
+    .await
+    .ok()
+    .flatten()
+    .flatten()
+}
+
+/// Runs `query` on a blocking worker, giving up after `timeout`.
+///
+/// `in_flight` tracks mount points whose query is still running, so a mount
+/// that is already stuck doesn't get another blocked thread piled onto it.
+/// Returns `Ok(None)` on a timeout or a query still in flight, and `Err` if
+/// the worker panicked.
+async fn run_guarded<T: Send + 'static>(
+    in_flight: &'static Mutex<HashSet<PathBuf>>,
+    mount_point: PathBuf,
+    timeout: Duration,
+    query: impl FnOnce(&Path) -> T + Send + 'static,
+) -> Result<Option<T>, tokio::task::JoinError> {
+    if !in_flight.lock().unwrap_or_else(PoisonError::into_inner).insert(mount_point.clone()) {
+        return Ok(None);
+    }
+
+    let worker = tokio::task::spawn_blocking(move || {
+        let guard = InFlightGuard { in_flight, mount_point };
+        query(&guard.mount_point)
+    });
+
+    match tokio::time::timeout(timeout, worker).await {
+        Ok(joined) => joined.map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Clears a mount point from an in-flight set when dropped, so it is
+/// cleared even if the query panics.
+struct InFlightGuard {
+    in_flight: &'static Mutex<HashSet<PathBuf>>,
+    mount_point: PathBuf,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.mount_point);
+    }
+}
+
 /// Formats bytes into a human-readable string (e.g., "1.5 GB").
 pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -68,3 +285,88 @@ pub fn format_bytes(bytes: u64) -> String {
         format!("{bytes} B")
     }
 }
+
+/// Formats a count with a metric suffix (e.g., "1.2M").
+pub fn format_count(count: u64) -> String {
+    const K: u64 = 1000;
+    const M: u64 = K * 1000;
+    const G: u64 = M * 1000;
+
+    if count >= G {
+        format!("{:.1}G", count as f64 / G as f64)
+    } else if count >= M {
+        format!("{:.1}M", count as f64 / M as f64)
+    } else if count >= K {
+        format!("{:.1}K", count as f64 / K as f64)
+    } else {
+        count.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inode_percentage() {
+        let space = SpaceInfo {
+            inodes_total: 1000,
+            inodes_used: 125,
+            ..Default::default()
+        };
+        assert!(space.has_inodes());
+        assert_eq!(space.inodes_percent_used(), 13);
+
+        // btrfs and similar report no fixed inode table
+        let space = SpaceInfo::default();
+        assert!(!space.has_inodes());
+        assert_eq!(space.inodes_percent_used(), 0);
+    }
+
+    #[test]
+    fn quota_prefers_soft_limit() {
+        let quota = QuotaInfo::from_limits(512, 100, 200, false).unwrap();
+        assert_eq!(quota.limit, 100 * QUOTA_BLOCK_SIZE);
+
+        let quota = QuotaInfo::from_limits(512, 0, 200, true).unwrap();
+        assert_eq!(quota.limit, 200 * QUOTA_BLOCK_SIZE);
+        assert!(quota.project);
+
+        assert!(QuotaInfo::from_limits(512, 0, 0, false).is_none());
+    }
+
+    #[test]
+    fn quota_percentage_is_capped() {
+        let quota = |used| QuotaInfo {
+            used,
+            limit: 1000,
+            project: false,
+        };
+        assert_eq!(quota(0).percent_used(), 0);
+        assert_eq!(quota(455).percent_used(), 46);
+        assert_eq!(quota(1500).percent_used(), 100);
+
+        let unlimited = QuotaInfo::default();
+        assert_eq!(unlimited.percent_used(), 0);
+    }
+
+    #[test]
+    fn panicked_query_is_no_longer_in_flight() {
+        static TEST_IN_FLIGHT: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Default::default);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mount_point = PathBuf::from("/mnt/test");
+        let timeout = Duration::from_secs(5);
+
+        let panicked = runtime.block_on(run_guarded(&TEST_IN_FLIGHT, mount_point.clone(), timeout, |_| {
+            panic!("query failed")
+        }));
+        assert!(panicked.is_err());
+
+        let answered = runtime.block_on(run_guarded(&TEST_IN_FLIGHT, mount_point, timeout, |_| 42));
+        assert_eq!(answered.unwrap(), Some(42));
+    }
+}
@@ -1,16 +1,23 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-//! UDisks2 D-Bus interface for device enumeration.
+//! UDisks2 D-Bus interface for device enumeration and drive actions.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use zbus::blocking::Connection;
+use futures_util::stream::{select_all, SelectAll};
+use futures_util::StreamExt;
+use zbus::message::Type as MessageType;
 use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::{Connection, MatchRule, MessageStream};
 
 const UDISKS2_DEST: &str = "org.freedesktop.UDisks2";
 const UDISKS2_PATH: &str = "/org/freedesktop/UDisks2";
+const FILESYSTEM_IFACE: &str = "org.freedesktop.UDisks2.Filesystem";
+const DRIVE_IFACE: &str = "org.freedesktop.UDisks2.Drive";
+const ATA_IFACE: &str = "org.freedesktop.UDisks2.Drive.Ata";
+const NVME_IFACE: &str = "org.freedesktop.UDisks2.NVMe.Controller";
 
 /// Information about a mounted filesystem.
 #[derive(Debug, Clone)]
@@ -22,16 +29,97 @@ pub struct DriveInfo {
     /// Device path (e.g., /dev/nvme0n1p1).
     pub device: String,
     /// Filesystem type (e.g., ext4, btrfs).
-    #[allow(dead_code)]
     pub fs_type: String,
     /// Drive model name, if available.
-    #[allow(dead_code)]
     pub model: Option<String>,
     /// Whether this is a removable drive.
     pub removable: bool,
+    /// Whether UDisks2 marks the device as a system device (`HintSystem`),
+    /// which only an administrator may unmount.
+    pub system: bool,
+    /// Filesystem UUID (`IdUUID`), if any.
+    pub uuid: Option<String>,
+    /// Partition UUID, if the filesystem is on a partition.
+    pub part_uuid: Option<String>,
+    /// Stable key for this drive's settings, see [`assign_ids`].
+    pub id: String,
+    /// UDisks2 object of the filesystem's block device.
+    pub object_path: Option<OwnedObjectPath>,
+    /// UDisks2 object of the physical drive, if any.
+    pub drive_path: Option<OwnedObjectPath>,
+    /// Whether the drive's media can be ejected.
+    pub ejectable: bool,
+    /// Whether the drive can be powered off.
+    pub can_power_off: bool,
+    /// SMART health of the physical drive, if it reports any.
+    pub health: Option<DriveHealth>,
+    /// Whether this is a network or FUSE mount rather than a local block device.
+    pub network: bool,
+    /// Other mount points of the same device folded into this one by
+    /// [`deduplicate_by_device`], such as btrfs subvolumes.
+    pub also_mounted_at: Vec<PathBuf>,
+}
+
+/// SMART health data from the ATA or NVMe interface of a drive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriveHealth {
+    /// True if SMART predicts the drive is failing.
+    pub failing: bool,
+    /// Temperature in degrees Celsius, if known.
+    pub temperature: Option<f64>,
+    /// Powered-on time in seconds, if known.
+    pub power_on_seconds: Option<u64>,
+    /// Number of reallocated or pending bad sectors, if known (ATA only).
+    pub bad_sectors: Option<i64>,
+}
+
+/// A filesystem that UDisks2 knows about but that is not mounted.
+#[derive(Debug, Clone)]
+pub struct UnmountedFs {
+    /// UDisks2 object of the filesystem's block device.
+    pub object_path: OwnedObjectPath,
+    /// Device path (e.g., /dev/sdb1).
+    pub device: String,
+    /// Filesystem label, if any.
+    pub label: Option<String>,
+    /// Filesystem type (e.g., ext4, vfat).
+    pub fs_type: String,
+    /// Size of the block device in bytes.
+    pub size: u64,
+}
+
+impl UnmountedFs {
+    /// Returns the label if set, otherwise the device path.
+    pub fn display_name(&self) -> String {
+        self.label
+            .clone()
+            .filter(|label| !label.is_empty())
+            .unwrap_or_else(|| self.device.clone())
+    }
+}
+
+/// Properties read from a UDisks2 Drive object.
+#[derive(Debug, Clone, Default)]
+struct DriveProps {
+    model: Option<String>,
+    removable: bool,
+    ejectable: bool,
+    can_power_off: bool,
+    health: Option<DriveHealth>,
 }
 
 impl DriveInfo {
+    /// Returns true if this is a ZFS dataset, whose capacity belongs to its pool.
+    pub fn is_zfs(&self) -> bool {
+        self.fs_type == "zfs"
+    }
+
+    /// Returns true if the card should offer unmount, eject and power off:
+    /// a UDisks2 device that is removable or not a system device, and not `/`.
+    pub fn user_unmountable(&self) -> bool {
+        self.object_path.is_some() && (self.removable || !self.system) && self.mount_point != Path::new("/")
+    }
+
     /// Returns a display name for this drive.
     ///
     /// Uses the label if available, otherwise derives a name from the mount point.
@@ -82,20 +170,36 @@ const EXCLUDED_FS_TYPES: &[&str] = &[
     "ramfs",
 ];
 
+/// Returns true if filesystems of this type are never shown.
+pub fn is_excluded_fs_type(fs_type: &str) -> bool {
+    EXCLUDED_FS_TYPES.iter().any(|&excluded| fs_type == excluded)
+}
+
 /// Enumerates all mounted filesystems via UDisks2.
 ///
 /// Deduplicates by device path, preferring root (/) and /home mounts
 /// over subvolume mounts like /var, /srv, etc.
-pub fn enumerate_drives() -> Result<Vec<DriveInfo>> {
+pub async fn enumerate_drives() -> Result<Vec<DriveInfo>> {
+    Ok(scan().await?.0)
+}
+
+/// Returns mounted drives and the filesystems that could be mounted.
+pub async fn scan() -> Result<(Vec<DriveInfo>, Vec<UnmountedFs>)> {
     let connection = Connection::system()
+        .await
         .context("failed to connect to system D-Bus")?;
 
-    let objects = get_managed_objects(&connection)?;
+    let objects = get_managed_objects(&connection).await?;
+    Ok((drives_from_objects(&objects)?, unmounted_from_objects(&objects)))
+}
+
+/// Builds the drive list from a UDisks2 managed objects snapshot.
+fn drives_from_objects(objects: &ManagedObjects) -> Result<Vec<DriveInfo>> {
     let mut drives = Vec::new();
 
-    for interfaces in objects.values() {
+    for (object_path, interfaces) in objects {
         // Only care about objects with a Filesystem interface
-        let Some(fs_props) = interfaces.get("org.freedesktop.UDisks2.Filesystem") else {
+        let Some(fs_props) = interfaces.get(FILESYSTEM_IFACE) else {
             continue;
         };
 
@@ -113,18 +217,28 @@ pub fn enumerate_drives() -> Result<Vec<DriveInfo>> {
         let device = get_string_prop(block_props, "Device")?;
         let label = get_string_prop(block_props, "IdLabel").ok();
         let fs_type = get_string_prop(block_props, "IdType").unwrap_or_default();
+        let uuid = get_string_prop(block_props, "IdUUID")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let system = get_bool_prop(block_props, "HintSystem");
+        let part_uuid = interfaces
+            .get("org.freedesktop.UDisks2.Partition")
+            .and_then(|props| get_string_prop(props, "UUID").ok())
+            .filter(|s| !s.is_empty());
 
         // Skip virtual/pseudo filesystems
-        if EXCLUDED_FS_TYPES.iter().any(|&excluded| fs_type == excluded) {
+        if is_excluded_fs_type(&fs_type) {
             continue;
         }
 
         // Get drive info if available
-        let (model, removable) = if let Ok(drive_path) = get_object_path_prop(block_props, "Drive") {
-            get_drive_info(&objects, &drive_path).unwrap_or((None, false))
-        } else {
-            (None, false)
-        };
+        let drive_path = get_object_path_prop(block_props, "Drive")
+            .ok()
+            .filter(|path| path.as_str() != "/");
+        let drive_props = drive_path
+            .as_ref()
+            .and_then(|path| get_drive_info(objects, path).ok())
+            .unwrap_or_default();
 
         // Create a DriveInfo for each mount point (usually just one)
         for mount_point in mount_points {
@@ -133,23 +247,254 @@ pub fn enumerate_drives() -> Result<Vec<DriveInfo>> {
                 label: label.clone(),
                 device: device.clone(),
                 fs_type: fs_type.clone(),
-                model: model.clone(),
-                removable,
+                model: drive_props.model.clone(),
+                removable: drive_props.removable,
+                system,
+                uuid: uuid.clone(),
+                part_uuid: part_uuid.clone(),
+                id: String::new(),
+                object_path: Some(object_path.clone()),
+                drive_path: drive_path.clone(),
+                ejectable: drive_props.ejectable,
+                can_power_off: drive_props.can_power_off,
+                health: drive_props.health.clone(),
+                network: false,
+                also_mounted_at: Vec::new(),
             });
         }
     }
 
     // Deduplicate by device - keep only the preferred mount point per device
     deduplicate_by_device(&mut drives);
+    assign_ids(&mut drives);
 
     Ok(drives)
 }
 
+/// Lists filesystems that are not mounted and could be mounted by the user.
+///
+/// Skips virtual filesystems and devices UDisks2 marks as ignored or as
+/// system devices, which only an administrator may mount.
+fn unmounted_from_objects(objects: &ManagedObjects) -> Vec<UnmountedFs> {
+    let mut unmounted: Vec<UnmountedFs> = objects
+        .iter()
+        .filter_map(|(object_path, interfaces)| {
+            let fs_props = interfaces.get(FILESYSTEM_IFACE)?;
+            let block_props = interfaces.get("org.freedesktop.UDisks2.Block")?;
+
+            if !get_mount_points(fs_props).ok()?.is_empty() {
+                return None;
+            }
+
+            if get_bool_prop(block_props, "HintIgnore") || get_bool_prop(block_props, "HintSystem") {
+                return None;
+            }
+
+            let fs_type = get_string_prop(block_props, "IdType").unwrap_or_default();
+            if is_excluded_fs_type(&fs_type) {
+                return None;
+            }
+
+            Some(UnmountedFs {
+                object_path: object_path.clone(),
+                device: get_string_prop(block_props, "Device").ok()?,
+                label: get_string_prop(block_props, "IdLabel").ok(),
+                fs_type,
+                size: block_props
+                    .get("Size")
+                    .and_then(|v| v.downcast_ref::<u64>().ok())
+                    .unwrap_or(0),
+            })
+        })
+        .collect();
+
+    unmounted.sort_by(|a, b| a.device.cmp(&b.device));
+    unmounted
+}
+
+/// Mounts a filesystem and returns where it was mounted.
+pub async fn mount(object_path: OwnedObjectPath) -> Result<PathBuf> {
+    let connection = Connection::system()
+        .await
+        .context("failed to connect to system D-Bus")?;
+
+    let reply = connection
+        .call_method(Some(UDISKS2_DEST), &object_path, Some(FILESYSTEM_IFACE), "Mount", &(no_options(),))
+        .await
+        .map_err(method_error)?;
+
+    let mount_point: String = reply.body().deserialize().context("invalid Mount reply")?;
+    Ok(PathBuf::from(mount_point))
+}
+
+/// Unmounts a filesystem.
+pub async fn unmount(object_path: OwnedObjectPath) -> Result<()> {
+    let connection = Connection::system()
+        .await
+        .context("failed to connect to system D-Bus")?;
+
+    unmount_on(&connection, &object_path).await
+}
+
+/// Unmounts a filesystem, then ejects the media from its drive.
+pub async fn eject(object_path: OwnedObjectPath, drive_path: OwnedObjectPath) -> Result<()> {
+    let connection = Connection::system()
+        .await
+        .context("failed to connect to system D-Bus")?;
+
+    unmount_on(&connection, &object_path).await?;
+    connection
+        .call_method(Some(UDISKS2_DEST), &drive_path, Some(DRIVE_IFACE), "Eject", &(no_options(),))
+        .await
+        .map_err(method_error)?;
+
+    Ok(())
+}
+
+/// Unmounts a filesystem, then powers off its drive so it can be unplugged.
+pub async fn power_off(object_path: OwnedObjectPath, drive_path: OwnedObjectPath) -> Result<()> {
+    let connection = Connection::system()
+        .await
+        .context("failed to connect to system D-Bus")?;
+
+    unmount_on(&connection, &object_path).await?;
+    connection
+        .call_method(Some(UDISKS2_DEST), &drive_path, Some(DRIVE_IFACE), "PowerOff", &(no_options(),))
+        .await
+        .map_err(method_error)?;
+
+    Ok(())
+}
+
+async fn unmount_on(connection: &Connection, object_path: &OwnedObjectPath) -> Result<()> {
+    connection
+        .call_method(Some(UDISKS2_DEST), object_path, Some(FILESYSTEM_IFACE), "Unmount", &(no_options(),))
+        .await
+        .map_err(method_error)?;
+
+    Ok(())
+}
+
+/// Empty `a{sv}` options for UDisks2 methods.
+fn no_options() -> HashMap<&'static str, Value<'static>> {
+    HashMap::new()
+}
+
+/// Turns a D-Bus method error into its human-readable message (e.g. "target is busy").
+fn method_error(err: zbus::Error) -> anyhow::Error {
+    match err {
+        zbus::Error::MethodError(_, Some(message), _) => anyhow::anyhow!(message),
+        other => anyhow::Error::new(other),
+    }
+}
+
+/// Long-lived UDisks2 connection that reports mount and unmount events.
+///
+/// Listens for ObjectManager `InterfacesAdded`/`InterfacesRemoved`, for
+/// `PropertiesChanged` on the Filesystem interface's `MountPoints`, and for
+/// SMART updates on the ATA and NVMe drive interfaces.
+pub struct MountWatcher {
+    connection: Connection,
+    signals: SelectAll<MessageStream>,
+}
+
+impl MountWatcher {
+    /// Connects to the system bus and subscribes to UDisks2 mount signals.
+    pub async fn new() -> Result<Self> {
+        let connection = Connection::system()
+            .await
+            .context("failed to connect to system D-Bus")?;
+
+        let object_manager_rule = |member: &'static str| -> Result<MatchRule<'static>> {
+            Ok(MatchRule::builder()
+                .msg_type(MessageType::Signal)
+                .sender(UDISKS2_DEST)?
+                .path(UDISKS2_PATH)?
+                .interface("org.freedesktop.DBus.ObjectManager")?
+                .member(member)?
+                .build())
+        };
+
+        let properties_rule = |iface: &'static str| -> Result<MatchRule<'static>> {
+            Ok(MatchRule::builder()
+                .msg_type(MessageType::Signal)
+                .sender(UDISKS2_DEST)?
+                .path_namespace(UDISKS2_PATH)?
+                .interface("org.freedesktop.DBus.Properties")?
+                .member("PropertiesChanged")?
+                .add_arg(iface)?
+                .build())
+        };
+
+        let mut streams = Vec::new();
+        for rule in [
+            object_manager_rule("InterfacesAdded")?,
+            object_manager_rule("InterfacesRemoved")?,
+            properties_rule(FILESYSTEM_IFACE)?,
+            properties_rule(ATA_IFACE)?,
+            properties_rule(NVME_IFACE)?,
+        ] {
+            let stream = MessageStream::for_match_rule(rule, &connection, None)
+                .await
+                .context("failed to subscribe to UDisks2 signals")?;
+            streams.push(stream);
+        }
+
+        Ok(Self {
+            connection,
+            signals: select_all(streams),
+        })
+    }
+
+    /// Returns mounted drives and the filesystems that could be mounted.
+    pub async fn scan(&self) -> Result<(Vec<DriveInfo>, Vec<UnmountedFs>)> {
+        let objects = get_managed_objects(&self.connection).await?;
+        Ok((drives_from_objects(&objects)?, unmounted_from_objects(&objects)))
+    }
+
+    /// Waits until a filesystem is mounted, unmounted, added or removed,
+    /// or a drive's SMART data is updated.
+    pub async fn changed(&mut self) -> Result<()> {
+        while let Some(message) = self.signals.next().await {
+            let message = message.context("failed to receive UDisks2 signal")?;
+            if is_relevant_change(&message) {
+                return Ok(());
+            }
+        }
+
+        anyhow::bail!("UDisks2 signal stream closed")
+    }
+}
+
+/// Returns true if a signal affects the set of mounted filesystems or drive health.
+fn is_relevant_change(message: &zbus::Message) -> bool {
+    let header = message.header();
+    let body = message.body();
+
+    match header.member().map(|m| m.as_str()) {
+        Some("InterfacesAdded") => body
+            .deserialize::<(OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>)>()
+            .is_ok_and(|(_, interfaces)| interfaces.contains_key(FILESYSTEM_IFACE)),
+        Some("InterfacesRemoved") => body
+            .deserialize::<(OwnedObjectPath, Vec<String>)>()
+            .is_ok_and(|(_, interfaces)| interfaces.iter().any(|i| i == FILESYSTEM_IFACE)),
+        Some("PropertiesChanged") => body
+            .deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+            .is_ok_and(|(iface, changed, invalidated)| {
+                iface != FILESYSTEM_IFACE
+                    || changed.contains_key("MountPoints")
+                    || invalidated.iter().any(|p| p == "MountPoints")
+            }),
+        _ => false,
+    }
+}
+
 /// Filters out subvolume mounts, keeping only primary mount points.
 ///
-/// Always keeps / and /home (even if same device). Discards other subvolumes
-/// like /var, /srv, etc. that share a device with / or /home.
-fn deduplicate_by_device(drives: &mut Vec<DriveInfo>) {
+/// Always keeps / and /home (even if same device). Other subvolumes like
+/// /var, /srv, etc. that share a device with / or /home are listed in the
+/// `also_mounted_at` of the first kept mount of that device instead.
+pub fn deduplicate_by_device(drives: &mut Vec<DriveInfo>) {
     use std::collections::HashSet;
 
     // First pass: find devices that have / or /home
@@ -162,6 +507,7 @@ fn deduplicate_by_device(drives: &mut Vec<DriveInfo>) {
     }
 
     // Second pass: keep primary mounts, and non-primary only if device has no primary
+    let mut folded: HashMap<String, Vec<PathBuf>> = HashMap::new();
     drives.retain(|drive| {
         let path = drive.mount_point.to_string_lossy();
         let is_primary = path == "/" || path.starts_with("/home");
@@ -170,18 +516,55 @@ fn deduplicate_by_device(drives: &mut Vec<DriveInfo>) {
             // Always keep / and /home
             true
         } else if devices_with_primary.contains(&drive.device) {
-            // This device has / or /home, skip other subvolumes
+            // This device has / or /home, fold other subvolumes into it
+            folded
+                .entry(drive.device.clone())
+                .or_default()
+                .push(drive.mount_point.clone());
             false
         } else {
             // Device has no primary mount, keep this one
             true
         }
     });
+
+    for drive in drives.iter_mut() {
+        if let Some(mounts) = folded.remove(&drive.device) {
+            drive.also_mounted_at = mounts;
+        }
+    }
+}
+
+/// Gives each drive a stable key for its settings.
+///
+/// Prefers the filesystem UUID, then the partition UUID, and falls back to the
+/// mount point. A filesystem kept at several mount points (e.g. btrfs / and
+/// /home) gets the mount point appended so each mount has its own settings.
+pub fn assign_ids(drives: &mut [DriveInfo]) {
+    let mut mounts_per_device: HashMap<String, usize> = HashMap::new();
+    for drive in drives.iter() {
+        *mounts_per_device.entry(drive.device.clone()).or_default() += 1;
+    }
+
+    for drive in drives.iter_mut() {
+        let base = drive
+            .uuid
+            .as_ref()
+            .map(|uuid| format!("UUID={uuid}"))
+            .or_else(|| drive.part_uuid.as_ref().map(|uuid| format!("PARTUUID={uuid}")));
+
+        let shared = mounts_per_device.get(&drive.device).copied().unwrap_or(0) > 1;
+        drive.id = match base {
+            Some(base) if shared => format!("{base}:{}", drive.mount_point.display()),
+            Some(base) => base,
+            None => drive.mount_point.display().to_string(),
+        };
+    }
 }
 
 type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
 
-fn get_managed_objects(connection: &Connection) -> Result<ManagedObjects> {
+async fn get_managed_objects(connection: &Connection) -> Result<ManagedObjects> {
     let reply = connection
         .call_method(
             Some(UDISKS2_DEST),
@@ -190,6 +573,7 @@ fn get_managed_objects(connection: &Connection) -> Result<ManagedObjects> {
             "GetManagedObjects",
             &(),
         )
+        .await
         .context("failed to call GetManagedObjects")?;
 
     reply.body().deserialize().context("failed to deserialize managed objects")
@@ -280,6 +664,14 @@ fn get_string_prop(props: &HashMap<String, OwnedValue>, key: &str) -> Result<Str
         .context(format!("property {key} is not a string"))
 }
 
+/// Returns a boolean property, or false if it is missing.
+fn get_bool_prop(props: &HashMap<String, OwnedValue>, key: &str) -> bool {
+    props
+        .get(key)
+        .and_then(|v| v.downcast_ref::<bool>().ok())
+        .unwrap_or(false)
+}
+
 fn get_object_path_prop(props: &HashMap<String, OwnedValue>, key: &str) -> Result<OwnedObjectPath> {
     let value = props
         .get(key)
@@ -292,16 +684,13 @@ fn get_object_path_prop(props: &HashMap<String, OwnedValue>, key: &str) -> Resul
         .context(format!("property {key} is not an object path"))
 }
 
-fn get_drive_info(
-    objects: &ManagedObjects,
-    drive_path: &OwnedObjectPath,
-) -> Result<(Option<String>, bool)> {
+fn get_drive_info(objects: &ManagedObjects, drive_path: &OwnedObjectPath) -> Result<DriveProps> {
     let interfaces = objects
         .get(drive_path)
         .context("drive object not found")?;
 
     let drive_props = interfaces
-        .get("org.freedesktop.UDisks2.Drive")
+        .get(DRIVE_IFACE)
         .context("no Drive interface")?;
 
     let model = drive_props
@@ -310,10 +699,75 @@ fn get_drive_info(
         .map(|s| s.to_string())
         .filter(|s| !s.is_empty());
 
-    let removable = drive_props
-        .get("Removable")
-        .and_then(|v| v.downcast_ref::<bool>().ok())
-        .unwrap_or(false);
+    let bool_prop = |key: &str| {
+        drive_props
+            .get(key)
+            .and_then(|v| v.downcast_ref::<bool>().ok())
+            .unwrap_or(false)
+    };
+
+    Ok(DriveProps {
+        model,
+        removable: bool_prop("Removable"),
+        ejectable: bool_prop("Ejectable"),
+        can_power_off: bool_prop("CanPowerOff"),
+        health: get_drive_health(interfaces),
+    })
+}
+
+/// Reads SMART data from a drive's ATA or NVMe controller interface.
+fn get_drive_health(interfaces: &HashMap<String, HashMap<String, OwnedValue>>) -> Option<DriveHealth> {
+    // UDisks2 reports temperatures in Kelvin, with 0 meaning unknown
+    let celsius = |kelvin: f64| (kelvin > 0.0).then(|| kelvin - 273.15);
+
+    if let Some(ata) = interfaces.get(ATA_IFACE) {
+        let bool_prop = |key: &str| ata.get(key).and_then(|v| v.downcast_ref::<bool>().ok());
+        if bool_prop("SmartSupported") != Some(true) || bool_prop("SmartEnabled") != Some(true) {
+            return None;
+        }
+
+        return Some(DriveHealth {
+            failing: bool_prop("SmartFailing").unwrap_or(false),
+            temperature: ata
+                .get("SmartTemperature")
+                .and_then(|v| v.downcast_ref::<f64>().ok())
+                .and_then(celsius),
+            power_on_seconds: ata
+                .get("SmartPowerOnSeconds")
+                .and_then(|v| v.downcast_ref::<u64>().ok())
+                .filter(|&secs| secs > 0),
+            bad_sectors: ata
+                .get("SmartNumBadSectors")
+                .and_then(|v| v.downcast_ref::<i64>().ok())
+                .filter(|&count| count >= 0),
+        });
+    }
+
+    let nvme = interfaces.get(NVME_IFACE)?;
+
+    // Any critical warning other than temperature means the controller is degrading
+    let warnings: Vec<String> = nvme
+        .get("SmartCriticalWarning")
+        .and_then(|v| Vec::<String>::try_from(v.try_clone().ok()?).ok())
+        .unwrap_or_default();
+    let updated = nvme
+        .get("SmartUpdated")
+        .and_then(|v| v.downcast_ref::<u64>().ok())
+        .unwrap_or(0);
+    if updated == 0 {
+        return None;
+    }
 
-    Ok((model, removable))
+    Some(DriveHealth {
+        failing: warnings.iter().any(|w| w != "temperature"),
+        temperature: nvme
+            .get("SmartTemperature")
+            .and_then(|v| v.downcast_ref::<u16>().ok())
+            .and_then(|kelvin| celsius(f64::from(kelvin))),
+        power_on_seconds: nvme
+            .get("SmartPowerOnHours")
+            .and_then(|v| v.downcast_ref::<u64>().ok())
+            .map(|hours| hours * 3600),
+        bad_sectors: None,
+    })
 }
@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! ZFS pool capacity, fragmentation and health from `zpool list`.
+//!
+//! statvfs on a ZFS dataset reports the dataset's own usage plus whatever
+//! the pool has free, so every dataset has a different "total". Pools are
+//! the unit that actually fills up, so CargoWatch groups datasets under
+//! their pool and alerts on pool capacity.
+
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::space::SpaceInfo;
+
+/// Capacity and health of one ZFS pool.
+#[derive(Debug, Clone)]
+pub struct PoolInfo {
+    /// Pool name.
+    pub name: String,
+    /// Pool size in bytes.
+    pub size: u64,
+    /// Allocated bytes.
+    pub allocated: u64,
+    /// Free bytes.
+    pub free: u64,
+    /// Free space fragmentation percentage, if the pool reports it.
+    pub fragmentation: Option<u8>,
+    /// Pool health (e.g. ONLINE, DEGRADED, FAULTED).
+    pub health: String,
+}
+
+impl PoolInfo {
+    /// Returns the settings id for this pool.
+    pub fn id(&self) -> String {
+        format!("zpool:{}", self.name)
+    }
+
+    /// Returns the pool's capacity as space info, so drive thresholds apply to it.
+    pub fn space(&self) -> SpaceInfo {
+        SpaceInfo {
+            total: self.size,
+            used: self.allocated,
+            available: self.free,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if every device in the pool is online.
+    pub fn is_healthy(&self) -> bool {
+        self.health == "ONLINE"
+    }
+}
+
+/// Returns the pool a dataset belongs to, e.g. `rpool` for `rpool/home/alice`.
+pub fn pool_name(dataset: &str) -> &str {
+    dataset.split('/').next().unwrap_or(dataset)
+}
+
+/// Lists imported pools by running `zpool list -Hp`.
+pub fn list_pools() -> Result<Vec<PoolInfo>> {
+    let output = Command::new("zpool")
+        .args(["list", "-Hp", "-o", "name,size,alloc,free,frag,health"])
+        .output()
+        .context("failed to run zpool")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "zpool list failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_line)
+        .collect())
+}
+
+/// Set while a `zpool list` is running on a worker thread.
+static IN_FLIGHT: AtomicBool = AtomicBool::new(false);
+
+/// Lists pools on a blocking worker, giving up after `timeout`.
+///
+/// `zpool` can block for a long time on a suspended pool, so this returns
+/// `Ok(None)` rather than waiting forever, or if an earlier listing is still
+/// hung.
+pub async fn list_pools_async(timeout: Duration) -> Result<Option<Vec<PoolInfo>>> {
+    // Don't pile another blocked thread onto a pool that is already stuck
+    if IN_FLIGHT.swap(true, Ordering::AcqRel) {
+        return Ok(None);
+    }
+
+    let task = tokio::task::spawn_blocking(|| {
+        let result = list_pools();
+        IN_FLIGHT.store(false, Ordering::Release);
+        result
+    });
+
+    match tokio::time::timeout(timeout, task).await {
+        Ok(result) => result.context("zpool task panicked")?.map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Parses one tab-separated line of `zpool list -Hp` output.
+fn parse_line(line: &str) -> Option<PoolInfo> {
+    let mut fields = line.split('\t');
+
+    Some(PoolInfo {
+        name: fields.next()?.to_string(),
+        size: fields.next()?.parse().ok()?,
+        allocated: fields.next()?.parse().ok()?,
+        free: fields.next()?.parse().ok()?,
+        // "-" when the pool has no fragmentation data
+        fragmentation: fields.next()?.parse().ok(),
+        health: fields.next()?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_healthy_pool() {
+        let pool = parse_line("rpool\t1000204886016\t400081954406\t600122931610\t12\tONLINE").unwrap();
+        assert_eq!(pool.name, "rpool");
+        assert_eq!(pool.size, 1_000_204_886_016);
+        assert_eq!(pool.allocated, 400_081_954_406);
+        assert_eq!(pool.free, 600_122_931_610);
+        assert_eq!(pool.fragmentation, Some(12));
+        assert!(pool.is_healthy());
+        assert_eq!(pool.id(), "zpool:rpool");
+    }
+
+    #[test]
+    fn parses_degraded_pool() {
+        let pool = parse_line("tank\t4000787030016\t3600708327014\t400078703002\t41\tDEGRADED").unwrap();
+        assert_eq!(pool.health, "DEGRADED");
+        assert!(!pool.is_healthy());
+        assert_eq!(pool.space().percent_used(), 90);
+    }
+
+    #[test]
+    fn parses_pool_without_fragmentation() {
+        let pool = parse_line("backup\t500107862016\t0\t500107862016\t-\tONLINE").unwrap();
+        assert_eq!(pool.fragmentation, None);
+    }
+
+    #[test]
+    fn rejects_empty_and_truncated_lines() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("rpool\t1000\t400").is_none());
+        assert!(parse_line("rpool\tbig\t400\t600\t12\tONLINE").is_none());
+    }
+
+    #[test]
+    fn finds_pool_name() {
+        assert_eq!(pool_name("rpool/home/alice"), "rpool");
+        assert_eq!(pool_name("tank"), "tank");
+    }
+}